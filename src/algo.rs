@@ -1,44 +1,76 @@
 use crate::{
-    grid::{Grid, HType},
-    node::Node
+    grid::{Grid, HType, Move, Topology},
+    node::Node,
+    state::State,
+    cost::CType,
+    weight::{WType, Weighting, Schedule},
 };
 use std::{
     fmt,
-    collections::{BinaryHeap, HashSet},
-    rc::Rc,
-    cell::RefCell,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    sync::atomic::{AtomicBool, Ordering as AtomicOrdering},
+    time::{Duration, Instant},
 };
 
+// Parallel IDA* splits the tree until it has this many branches per thread, so that a thread
+// which finishes early still has work to take.
+const BRANCHES_PER_JOB: usize = 8;
+
 pub struct Algo
 {
-    open_list: BinaryHeap<Rc<RefCell<Node>>>,
-    closed_list: HashSet<Node>,
-    path: Vec<Rc<RefCell<Node>>>,
-    solution: Option<Rc<RefCell<Node>>>,
+    open_list: BinaryHeap<Node>,
+    // Lowest cost pushed in the open list for each grid, a worse duplicate is never pushed.
+    open_costs: HashMap<Grid, u32>,
+    closed_list: HashSet<Grid>,
+    // Every node expanded by A* or reached by BFS, the `parent` of a node is its index in this list.
+    expanded: Vec<Node>,
+    // Current branch of IDA*, the `parent` of a node is the index of the previous one.
+    path: Vec<Node>,
+    solution: Option<Node>,
     goal: Grid,
     h_type: HType,
     a_type: AType,
+    c_type: CType,
     t_complex: u64,
     s_complex: u64,
     weight: u32,
     max_weight: u32,
+    w_type: WType,
+    // Unweighted heuristic of the initial grid, the depth the dynamic weighting plans for.
+    anticipated: u32,
     g_max: u32,
-    greedy: bool
+    greedy: bool,
+    // Number of threads used by IDA*.
+    jobs: usize,
+    // Set when a node was not expanded because of `g_max`.
+    limit_reached: bool,
+    // ARA* stops improving its solution after this long.
+    time_limit: Option<Duration>,
+    improvements: Vec<Improvement>
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
-pub enum AType
+// A solution published by ARA*, at most `bound` times as expensive as an optimal one.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Improvement
 {
-    AStar,
-    IDAStar
+    pub cost: u32,
+    pub weight: u32,
+    pub bound: f64,
+    pub elapsed: Duration
 }
 
-impl Default for AType
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AType
 {
-    fn default() -> Self
-    {
-        Self::IDAStar
-    }
+    AStar,
+    #[default]
+    IDAStar,
+    // Breadth-first, the heuristic is left out and the solution has the fewest moves whatever they cost.
+    Bfs,
+    // Anytime A*, starts from the highest weight and lowers it while there is time left.
+    AraStar
 }
 
 impl fmt::Display for AType
@@ -47,7 +79,9 @@ impl fmt::Display for AType
         match self
         {
             Self::AStar => write!(f, "A*"),
-            Self::IDAStar => write!(f, "IDA*")
+            Self::IDAStar => write!(f, "IDA*"),
+            Self::Bfs => write!(f, "BFS"),
+            Self::AraStar => write!(f, "ARA*")
         }
     }
 }
@@ -61,35 +95,122 @@ impl AType
             None => Ok(Self::default()),
             Some("astar") => Ok(Self::AStar),
             Some("idastar") => Ok(Self::IDAStar),
+            Some("bfs") => Ok(Self::Bfs),
+            Some("arastar") => Ok(Self::AraStar),
             Some(h) => Err(format!("This algorithmic function does not exist: {}", h))
         }
     }
 }
 
+// Solver options shared by every puzzle of a run.
+#[derive(Clone, Debug)]
+pub struct Settings
+{
+    pub h_type: HType,
+    pub a_type: AType,
+    pub c_type: CType,
+    pub w_type: WType,
+    pub topology: Topology,
+    // `None` picks a weight depending on the size of the puzzle.
+    pub max_weight: Option<u32>,
+    pub g_max: u32,
+    pub greedy: bool,
+    // Threads used to solve a single puzzle (only IDA* can use more than one).
+    pub jobs: usize,
+    // Only used by ARA*: how long it keeps improving its solution once it has found the first one.
+    pub time_limit: Option<Duration>
+}
+
+impl Default for Settings
+{
+    fn default() -> Self
+    {
+        Settings
+        {
+            h_type: HType::default(),
+            a_type: AType::default(),
+            c_type: CType::default(),
+            w_type: WType::default(),
+            topology: Topology::default(),
+            max_weight: None,
+            g_max: u32::MAX,
+            greedy: false,
+            jobs: 1,
+            time_limit: None
+        }
+    }
+}
+
+impl Settings
+{
+    pub fn get_max_weight(&self, lines: u8) -> u32
+    {
+        match self.max_weight
+        {
+            _ if self.greedy => 1,
+            Some(weight) => weight,
+            None => u32::from(lines) / 2 + 1
+        }
+    }
+}
+
 impl Algo
 {
+    pub fn with_settings(grid: Grid, goal: Grid, settings: &Settings) -> Self
+    {
+        let max_weight = settings.get_max_weight(grid.get_lines());
+        // ARA* lowers the weight on its own and BFS has no use for the heuristic.
+        let w_type = match settings.a_type
+        {
+            AType::AStar | AType::IDAStar => settings.w_type,
+            AType::Bfs | AType::AraStar => WType::Progressive
+        };
+        let weight = if w_type == WType::Progressive { 1 } else { max_weight };
+        let mut initial_node = Node::new(State::default(), grid);
+        initial_node.update_state(&goal, settings.h_type, 1, settings.greedy, &settings.c_type);
+        let anticipated = initial_node.state.h;
+        if !settings.greedy
+        {
+            Weighting { w_type, weight, anticipated }.apply(&mut initial_node.state);
+        }
+        let mut algo = Self::new(initial_node, goal, settings.h_type, settings.a_type, settings.c_type.clone(), weight, max_weight, settings.g_max, settings.greedy);
+        algo.w_type = w_type;
+        algo.anticipated = anticipated;
+        algo.jobs = settings.jobs;
+        algo.time_limit = settings.time_limit;
+        algo
+    }
+
     #[allow(clippy::too_many_arguments)]
-    pub fn new(initial_node: Node, goal: Grid, h_type: HType, a_type: AType, min_weight: u32, max_weight: u32, g_max: u32, greedy: bool) -> Self
+    pub fn new(initial_node: Node, goal: Grid, h_type: HType, a_type: AType, c_type: CType, min_weight: u32, max_weight: u32, g_max: u32, greedy: bool) -> Self
     {
-        let initial_node = Rc::new(RefCell::new(initial_node));
         let mut open_list = BinaryHeap::new();
-        open_list.push(Rc::clone(&initial_node));
+        open_list.push(initial_node.clone());
 
         Algo
         {
             open_list,
+            open_costs: HashMap::new(),
             closed_list: HashSet::new(),
+            expanded: Vec::new(),
             path: vec![initial_node],
             solution: None,
             goal,
             h_type,
             a_type,
+            c_type,
             t_complex: 0,
             s_complex: 0,
             weight: min_weight,
             max_weight,
+            w_type: WType::Progressive,
+            anticipated: 0,
             g_max,
-            greedy
+            greedy,
+            jobs: 1,
+            limit_reached: false,
+            time_limit: None,
+            improvements: Vec::new()
         }
     }
 
@@ -107,7 +228,7 @@ impl Algo
     {
         if let Some(sol) = self.solution.as_ref()
         {
-            sol.borrow().state.g
+            sol.state.g
         }
         else {
             0
@@ -118,7 +239,7 @@ impl Algo
     {
         if let Some(node) = self.path.last()
         {
-            node.borrow().state.g
+            node.state.g
         }
         else {
             0
@@ -129,7 +250,7 @@ impl Algo
     {
         match self.a_type
         {
-            AType::AStar => self.get_total_cost_a_star(),
+            AType::AStar | AType::Bfs | AType::AraStar => self.get_total_cost_a_star(),
             AType::IDAStar => self.get_total_cost_ida_star()
         }
     }
@@ -139,122 +260,216 @@ impl Algo
         self.weight
     }
 
-    pub fn print_steps(&self)
+    // At most how many times the optimal cost the solution costs, `None` when nothing is guaranteed.
+    // The progressive policy is bounded by the highest weight it reached.
+    pub fn get_bound(&self) -> Option<f64>
     {
         match self.a_type
         {
-            AType::AStar => self.print_steps_a_star(),
-            AType::IDAStar => self.print_steps_ida_star()
+            // BFS ignores the heuristic, greedy or not.
+            AType::Bfs if self.c_type != CType::Unit => None,
+            AType::Bfs => Some(1.0),
+            _ if self.greedy => None,
+            AType::AraStar => self.improvements.last().map(|i| i.bound),
+            AType::AStar | AType::IDAStar => Some(self.weight as f64)
         }
     }
 
-    pub fn print_steps_a_star(&self)
+    fn weighting(&self) -> Weighting
     {
-        if let Some(sol) = self.solution.as_ref()
-        {
-            sol.borrow().print_steps();
-        }
+        Weighting { w_type: self.w_type, weight: self.weight, anticipated: self.anticipated }
     }
 
-    pub fn print_steps_ida_star(&self)
+    pub fn get_steps(&self) -> Vec<Grid>
     {
-        if !self.path.is_empty()
+        match self.a_type
         {
-            for node in self.path.iter()
-            {
-                println!("{}", node.borrow().grid);
-                println!("===================================\n");
-            }   
+            AType::AStar | AType::Bfs | AType::AraStar => {
+                let mut steps: Vec<Grid> = Vec::new();
+                let mut node = self.solution.as_ref();
+                while let Some(n) = node
+                {
+                    steps.push(n.grid.clone());
+                    node = n.parent.map(|p| &self.expanded[p]);
+                }
+                steps.reverse();
+                steps
+            },
+            AType::IDAStar => self.path.iter().map(|node| node.grid.clone()).collect()
         }
     }
 
-    fn explore_node(&mut self, threshold: u64) -> (u64, bool)
+    // Each move is given with the index of the blank that moved, which only matters with several blanks.
+    pub fn get_moves(&self) -> Vec<(usize, Move)>
     {
-        if self.path.last().is_none()
-        {
-            return (u64::MAX, false);
-        }
+        self.get_steps().windows(2).filter_map(|w| w[0].get_move_to(&w[1])).collect()
+    }
 
-        self.t_complex += 1;
-        let node = self.path.last().unwrap();
-        let curr_f = node.borrow().state.f;
+    // Every solution found by ARA*, from the first one to the best one.
+    pub fn get_improvements(&self) -> &[Improvement]
+    {
+        &self.improvements
+    }
 
-        if curr_f > threshold
-        {
-            return (curr_f, false);
-        }
-        else if node.borrow().state.h == 0
-        {
-            return (0, false);
-        }
-        else if node.borrow().state.g >= self.g_max
+    // Whether the last failed search was cut short by the uniform cost limit.
+    pub fn limit_reached(&self) -> bool
+    {
+        self.limit_reached
+    }
+
+    pub fn print_steps(&self)
+    {
+        for grid in self.get_steps()
         {
-            return (curr_f, true);
+            println!("{}", grid);
+            println!("===================================\n");
         }
-        let childs: BinaryHeap<Rc<RefCell<Node>>> = Node::generate_childs(Rc::clone(node)).into_iter().map(|c| {
-            c.borrow_mut().update_state(&self.goal, self.h_type, self.weight as u32, self.greedy);
-            Rc::clone(&c)
-        }).collect();
-        let s_complex = self.path.len() as u64 + childs.len() as u64;
-        if s_complex > self.s_complex
+    }
+
+    fn explorer<'a>(&'a self, found: Option<&'a AtomicBool>) -> Explorer<'a>
+    {
+        Explorer
         {
-            self.s_complex = s_complex;
+            goal: &self.goal,
+            h_type: self.h_type,
+            c_type: &self.c_type,
+            weighting: self.weighting(),
+            greedy: self.greedy,
+            g_max: self.g_max,
+            found,
+            t_complex: 0,
+            s_complex: 0
         }
+    }
+
+    fn explore_node(&mut self, threshold: u64) -> (u64, bool)
+    {
+        let mut path = std::mem::take(&mut self.path);
+        let mut explorer = self.explorer(None);
+        let ret = explorer.explore(&mut path, threshold);
+        let (t_complex, s_complex) = (explorer.t_complex, explorer.s_complex);
+        self.path = path;
+        self.t_complex += t_complex;
+        self.s_complex = self.s_complex.max(s_complex);
+        ret
+    }
+
+    // Same as `explore_node` but the tree is split into branches at a shallow depth, which are
+    // explored by `jobs` threads. The first thread reaching the goal stops the others, the
+    // solution is optimal for this threshold as every branch is bounded by it.
+    fn explore_node_parallel(&mut self, threshold: u64, jobs: usize) -> (u64, bool)
+    {
         let mut lowest_f = u64::MAX;
         let mut g_max_reached = false;
-        for child in childs
+        let mut branches: Vec<Vec<Node>> = vec![self.path.clone()];
+
+        // Breadth first until there is enough work for every thread.
+        while !branches.is_empty() && branches.len() < jobs * BRANCHES_PER_JOB
         {
-            if !self.path.contains(&child)
+            let mut next: Vec<Vec<Node>> = Vec::new();
+            for branch in branches
             {
-                self.path.push(Rc::clone(&child));
-                let (recurs_res, g_max_reached_res) = self.explore_node(threshold);
-                if recurs_res == 0
+                let node = branch.last().unwrap();
+                self.t_complex += 1;
+                if node.state.f > threshold
+                {
+                    lowest_f = lowest_f.min(node.state.f);
+                    continue;
+                }
+                else if node.state.h == 0
                 {
+                    self.path = branch;
                     return (0, false);
                 }
-                else if recurs_res < lowest_f
+                else if node.state.g >= self.g_max
                 {
-                    lowest_f = recurs_res;
+                    lowest_f = lowest_f.min(node.state.f);
+                    g_max_reached = true;
+                    continue;
                 }
-                if !g_max_reached
+                for mut child in node.generate_childs(branch.len() - 1, &self.c_type)
                 {
-                    g_max_reached = g_max_reached_res;
+                    if !branch.contains(&child)
+                    {
+                        evaluate(&mut child, &self.goal, self.h_type, &self.c_type, self.greedy, self.weighting());
+                        let mut child_branch = branch.clone();
+                        child_branch.push(child);
+                        next.push(child_branch);
+                    }
                 }
-                self.path.pop();
             }
+            branches = next;
+            self.s_complex = self.s_complex.max(branches.iter().map(|b| b.len() as u64).sum());
+        }
+        // Most promising branches first, like the sequential search does with the children.
+        branches.sort_by(|a, b| b.last().cmp(&a.last()));
+
+        let found = AtomicBool::new(false);
+        let algo = &*self;
+        let results = utils::run_parallel(branches, jobs, |mut branch| {
+            let mut explorer = algo.explorer(Some(&found));
+            let (recurs_res, g_max_reached_res) = explorer.explore(&mut branch, threshold);
+            if recurs_res == 0
+            {
+                found.store(true, AtomicOrdering::Relaxed);
+            }
+            (recurs_res, g_max_reached_res, explorer.t_complex, explorer.s_complex, branch)
+        });
+
+        let mut solution: Option<Vec<Node>> = None;
+        let mut s_complex = 0;
+        for (recurs_res, g_max_reached_res, t, s, branch) in results
+        {
+            self.t_complex += t;
+            // Threads run at the same time so their memory adds up.
+            s_complex += s;
+            if recurs_res == 0 && solution.is_none()
+            {
+                solution = Some(branch);
+            }
+            lowest_f = lowest_f.min(recurs_res);
+            g_max_reached |= g_max_reached_res;
+        }
+        self.s_complex = self.s_complex.max(s_complex);
+        match solution
+        {
+            Some(path) => {
+                self.path = path;
+                (0, false)
+            },
+            None => (lowest_f, g_max_reached)
         }
-        (lowest_f, g_max_reached)
     }
 
     pub fn resolve_ida_star(&mut self) -> bool
     {
-        let mut threshold = u64::from(self.path.last().unwrap().borrow().state.h);
-        let mut threshold_change_count = 0;
-        let mut threshold_change_max = 1;
+        let mut threshold = self.path.last().unwrap().state.f;
+        let mut schedule = Schedule::new(1, 1);
         self.s_complex += 1;
 
         loop
         {
-            let (recurs_res, g_max_reached) = self.explore_node(threshold);
+            let (recurs_res, g_max_reached) = if self.jobs > 1
+            {
+                self.explore_node_parallel(threshold, self.jobs)
+            }
+            else {
+                self.explore_node(threshold)
+            };
             if recurs_res == 0
             {
                 return true;
             }
             else if recurs_res == u64::MAX || g_max_reached
             {
+                self.limit_reached = g_max_reached;
                 return false;
             }
             else {
                 threshold = recurs_res;
-                threshold_change_count += 1;
-                if threshold_change_count >= threshold_change_max && self.weight < self.max_weight
+                if self.w_type == WType::Progressive
                 {
-                    self.weight += 1;
-                    if self.weight % 5 == 0
-                    {
-                        threshold_change_max += 1;
-                    }
-                    threshold_change_count = 0;
+                    self.weight = schedule.tick(self.weight, self.max_weight);
                 }
             }
         }
@@ -262,66 +477,50 @@ impl Algo
 
     pub fn resolve_a_star(&mut self) -> bool
     {
-        let mut poped_node_count = 0;
-        let mut poped_node_max = 10;
+        let mut schedule = Schedule::new(10, 10);
 
         while let Some(node) = self.open_list.pop()
         {
-            poped_node_count += 1;
-            if poped_node_count >= poped_node_max && self.weight < self.max_weight
+            if self.w_type == WType::Progressive
             {
-                self.weight += 1;
-                if self.weight % 5 == 0
-                {
-                    poped_node_max += 10;
-                }
-                poped_node_count = 0;
+                self.weight = schedule.tick(self.weight, self.max_weight);
+            }
+            // A better duplicate of this grid has already been expanded.
+            if self.closed_list.contains(&node.grid)
+            {
+                continue;
             }
             self.t_complex += 1;
-            if node.borrow().state.h == 0 && node.borrow().grid == self.goal
+            if node.state.h == 0 && node.grid == self.goal
             {
                 self.solution = Some(node);
                 return true;
             }
-            self.closed_list.insert(node.borrow().clone());
-            if node.borrow().state.g < self.g_max
+            self.closed_list.insert(node.grid.clone());
+            let index = self.expanded.len();
+            self.expanded.push(node);
+            let node = &self.expanded[index];
+            if node.state.g < self.g_max
             {
-                for child in Node::generate_childs(node)
+                for mut child in node.generate_childs(index, &self.c_type)
                 {
-                    // if self.closed_list.iter().any(|n| n.grid == child.borrow().grid)
-                    if self.closed_list.contains(&child.borrow())
+                    if self.closed_list.contains(&child.grid) || self.open_costs.get(&child.grid).is_some_and(|&g| g <= child.state.g)
                     {
                         continue;
                     }
-                    // Try to swap the two conditions below (and do thefor loop directly, no if around it)
-                    else if self.open_list.iter().any(|n| *n == child)
+                    evaluate(&mut child, &self.goal, self.h_type, &self.c_type, self.greedy, self.weighting());
+                    if child.state.h == 0
                     {
-                        if self.open_list.iter().any(|n| *n == child && n.borrow().state.g < child.borrow().state.g)
-                        {
-                            continue;
-                        }
-                        let child_g = child.borrow().state.g;
-                        let child_parent = Rc::clone(child.borrow().parent.as_ref().unwrap());
-
-                        for node in self.open_list.iter().filter(|&n| *n == child && n.borrow().state.g < child.borrow().state.g)
-                        {
-                            let new_f = u64::from(node.borrow().state.h) + u64::from(child_g);
-                            node.borrow_mut().state.g = child_g;
-                            node.borrow_mut().state.f = new_f;
-                            node.borrow_mut().parent = Some(Rc::clone(&child_parent));
-                        }
-                    }
-                    else {
-                        child.borrow_mut().update_state(&self.goal, self.h_type, self.weight as u32, self.greedy);
-                        if child.borrow().state.h == 0
-                        {
-                            self.solution = Some(child);
-                            return true;
-                        }
-                        self.open_list.push(child)
+                        self.solution = Some(child);
+                        return true;
                     }
+                    self.open_costs.insert(child.grid.clone(), child.state.g);
+                    self.open_list.push(child);
                 }
             }
+            else {
+                self.limit_reached = true;
+            }
             let max_states = self.open_list.len() + self.closed_list.len();
             if self.s_complex < max_states as u64
             {
@@ -331,21 +530,425 @@ impl Algo
         false
     }
 
+    // Layer by layer from the initial grid, a grid is never queued twice.
+    pub fn resolve_bfs(&mut self) -> bool
+    {
+        let mut queue: VecDeque<usize> = VecDeque::new();
+        if let Some(node) = self.open_list.pop()
+        {
+            if node.grid == self.goal
+            {
+                self.solution = Some(node);
+                return true;
+            }
+            self.closed_list.insert(node.grid.clone());
+            self.expanded.push(node);
+            queue.push_back(0);
+        }
+
+        while let Some(index) = queue.pop_front()
+        {
+            self.t_complex += 1;
+            let node = &self.expanded[index];
+            if node.state.g >= self.g_max
+            {
+                self.limit_reached = true;
+                continue;
+            }
+            for child in node.generate_childs(index, &self.c_type)
+            {
+                if self.closed_list.contains(&child.grid)
+                {
+                    continue;
+                }
+                if child.grid == self.goal
+                {
+                    self.solution = Some(child);
+                    return true;
+                }
+                self.closed_list.insert(child.grid.clone());
+                queue.push_back(self.expanded.len());
+                self.expanded.push(child);
+            }
+            let max_states = self.closed_list.len();
+            if self.s_complex < max_states as u64
+            {
+                self.s_complex = max_states as u64;
+            }
+        }
+        false
+    }
+
+    // Heuristics are stored already multiplied by the weight.
+    fn reweight(&mut self, nodes: Vec<Node>, weight: u32)
+    {
+        for mut node in nodes
+        {
+            node.state.h = node.state.h / self.weight * weight;
+            node.state.f = node.state.g as u64 + node.state.h as u64;
+            self.open_list.push(node);
+        }
+        self.weight = weight;
+    }
+
+    // One ARA* search at the current weight, stops once no node of the open list can improve the
+    // solution. Nodes whose cost went down after they were expanded wait in `incons` for the next search.
+    // Returns false when the time ran out.
+    fn improve_path(&mut self, incons: &mut Vec<Node>, deadline: Option<Instant>) -> bool
+    {
+        while let Some(node) = self.open_list.peek()
+        {
+            if let Some(solution) = &self.solution
+            {
+                if node.state.f >= solution.state.g as u64
+                {
+                    break;
+                }
+                if deadline.is_some_and(|d| Instant::now() >= d)
+                {
+                    return false;
+                }
+            }
+            let node = self.open_list.pop().unwrap();
+            // A cheaper way to this grid was found after this node was pushed.
+            if self.closed_list.contains(&node.grid) || self.open_costs.get(&node.grid).is_some_and(|&g| g < node.state.g)
+            {
+                continue;
+            }
+            self.t_complex += 1;
+            self.closed_list.insert(node.grid.clone());
+            let index = self.expanded.len();
+            self.expanded.push(node);
+            let node = &self.expanded[index];
+            if node.state.g >= self.g_max
+            {
+                self.limit_reached = true;
+                continue;
+            }
+            for mut child in node.generate_childs(index, &self.c_type)
+            {
+                if self.open_costs.get(&child.grid).is_some_and(|&g| g <= child.state.g)
+                {
+                    continue;
+                }
+                self.open_costs.insert(child.grid.clone(), child.state.g);
+                child.update_state(&self.goal, self.h_type, self.weight, false, &self.c_type);
+                if child.grid == self.goal
+                {
+                    if self.solution.as_ref().is_none_or(|s| child.state.g < s.state.g)
+                    {
+                        self.solution = Some(child);
+                    }
+                }
+                else if self.closed_list.contains(&child.grid)
+                {
+                    incons.push(child);
+                }
+                else {
+                    self.open_list.push(child);
+                }
+            }
+            let max_states = self.open_list.len() + self.closed_list.len() + incons.len();
+            if self.s_complex < max_states as u64
+            {
+                self.s_complex = max_states as u64;
+            }
+        }
+        true
+    }
+
+    // Lowest cost any solution can have given the nodes left to expand, `None` when there are none.
+    fn lower_bound(&self, incons: &[Node]) -> Option<u64>
+    {
+        self.open_list.iter().chain(incons)
+            .filter(|n| self.open_costs.get(&n.grid).is_none_or(|&g| g == n.state.g))
+            .map(|n| n.state.g as u64 + (n.state.h / self.weight) as u64)
+            .min()
+    }
+
+    // Anytime weighted A*: a first solution is found quickly with the highest weight, then the weight
+    // goes down by one for each new search, which starts from what the previous one left.
+    // `found` is called with each improvement as soon as it is published.
+    pub fn resolve_ara_star(&mut self, mut found: impl FnMut(&Improvement)) -> bool
+    {
+        let start = Instant::now();
+        // The time limit counts from the first solution, the first search always goes to its end.
+        let mut deadline: Option<Instant> = None;
+        let mut incons: Vec<Node> = Vec::new();
+        let initial: Vec<Node> = self.open_list.drain().collect();
+        if let Some(node) = initial.first()
+        {
+            if node.grid == self.goal
+            {
+                self.solution = Some(node.clone());
+                self.publish(Improvement { cost: 0, weight: self.weight, bound: 1.0, elapsed: start.elapsed() }, &mut found);
+                return true;
+            }
+            self.open_costs.insert(node.grid.clone(), node.state.g);
+        }
+        let max_weight = self.max_weight.max(1);
+        self.reweight(initial, max_weight);
+
+        loop
+        {
+            self.closed_list.clear();
+            let finished = self.improve_path(&mut incons, deadline);
+            let cost = match &self.solution
+            {
+                Some(solution) => solution.state.g,
+                None => return false
+            };
+            let mut bound = match self.lower_bound(&incons)
+            {
+                Some(lower) if lower > 0 => (cost as f64 / lower as f64).max(1.0),
+                _ => 1.0
+            };
+            // The weight only bounds the solution of a search which went to its end.
+            if finished
+            {
+                bound = bound.min(self.weight as f64);
+            }
+            if let Some(last) = self.improvements.last()
+            {
+                bound = bound.min(last.bound);
+            }
+            if self.improvements.last().is_none_or(|i| cost < i.cost || bound < i.bound)
+            {
+                self.publish(Improvement { cost, weight: self.weight, bound, elapsed: start.elapsed() }, &mut found);
+            }
+            deadline = deadline.or_else(|| self.time_limit.map(|limit| Instant::now() + limit));
+            if !finished || bound <= 1.0 || self.weight <= 1
+            {
+                return true;
+            }
+            // Any weight above the bound would only find the same solution again.
+            let weight = (self.weight - 1).min(bound as u32).max(1);
+            let mut nodes: Vec<Node> = self.open_list.drain().collect();
+            nodes.append(&mut incons);
+            self.reweight(nodes, weight);
+        }
+    }
+
+    fn publish(&mut self, improvement: Improvement, found: &mut impl FnMut(&Improvement))
+    {
+        found(&improvement);
+        self.improvements.push(improvement);
+    }
+
     pub fn resolve(&mut self) -> bool
+    {
+        self.resolve_with(|_| ())
+    }
+
+    // Same as `resolve`, ARA* hands each improved solution to `found` as soon as it has it.
+    pub fn resolve_with(&mut self, found: impl FnMut(&Improvement)) -> bool
     {
         match self.a_type
         {
             AType::AStar => self.resolve_a_star(),
-            AType::IDAStar => self.resolve_ida_star()
+            AType::IDAStar => self.resolve_ida_star(),
+            AType::Bfs => self.resolve_bfs(),
+            AType::AraStar => self.resolve_ara_star(found)
+        }
+    }
+}
+
+// Heuristic of `node` weighted by the policy, a greedy search only looks at the heuristic.
+fn evaluate(node: &mut Node, goal: &Grid, h_type: HType, c_type: &CType, greedy: bool, weighting: Weighting)
+{
+    node.update_state(goal, h_type, 1, greedy, c_type);
+    if !greedy
+    {
+        weighting.apply(&mut node.state);
+    }
+}
+
+// Depth-first search of one IDA* iteration, kept apart from `Algo` so that several branches
+// can be explored at the same time on different threads.
+struct Explorer<'a>
+{
+    goal: &'a Grid,
+    h_type: HType,
+    c_type: &'a CType,
+    weighting: Weighting,
+    greedy: bool,
+    g_max: u32,
+    // Set once a thread reaches the goal, the others give up their branch.
+    found: Option<&'a AtomicBool>,
+    t_complex: u64,
+    s_complex: u64
+}
+
+impl<'a> Explorer<'a>
+{
+    // Explores everything under the last node of `path` up to `threshold`. Returns 0 when the goal is
+    // reached (`path` then leads to it), otherwise the lowest f above the threshold and whether the
+    // uniform cost limit was reached.
+    fn explore(&mut self, path: &mut Vec<Node>, threshold: u64) -> (u64, bool)
+    {
+        if path.last().is_none() || self.found.is_some_and(|f| f.load(AtomicOrdering::Relaxed))
+        {
+            return (u64::MAX, false);
+        }
+
+        self.t_complex += 1;
+        let node = path.last().unwrap();
+        let curr_f = node.state.f;
+
+        if curr_f > threshold
+        {
+            return (curr_f, false);
         }
+        else if node.state.h == 0
+        {
+            return (0, false);
+        }
+        else if node.state.g >= self.g_max
+        {
+            return (curr_f, true);
+        }
+        let childs: BinaryHeap<Node> = node.generate_childs(path.len() - 1, self.c_type).into_iter().map(|mut c| {
+            evaluate(&mut c, self.goal, self.h_type, self.c_type, self.greedy, self.weighting);
+            c
+        }).collect();
+        let s_complex = path.len() as u64 + childs.len() as u64;
+        if s_complex > self.s_complex
+        {
+            self.s_complex = s_complex;
+        }
+        let mut lowest_f = u64::MAX;
+        let mut g_max_reached = false;
+        for child in childs
+        {
+            if !path.contains(&child)
+            {
+                path.push(child);
+                let (recurs_res, g_max_reached_res) = self.explore(path, threshold);
+                if recurs_res == 0
+                {
+                    return (0, false);
+                }
+                else if recurs_res < lowest_f
+                {
+                    lowest_f = recurs_res;
+                }
+                if !g_max_reached
+                {
+                    g_max_reached = g_max_reached_res;
+                }
+                path.pop();
+            }
+        }
+        (lowest_f, g_max_reached)
     }
 }
 
 #[cfg(test)]
 mod tests
 {
-    use crate::state::State;
-    use std::{collections::{BinaryHeap, BTreeSet}, rc::Rc};
+    use super::*;
+    use crate::fixtures::{four_by_four, FOUR_BY_FOUR_COST};
+    use std::{collections::BTreeSet, rc::Rc};
+
+    // Splitting the iterations across threads must not cost optimality, whatever the thread count.
+    #[test]
+    fn parallel_ida_star()
+    {
+        let (grid, goal) = four_by_four();
+        let settings = Settings { max_weight: Some(1), ..Settings::default() };
+        for jobs in [2, 4, 7]
+        {
+            let mut parallel = Algo::with_settings(grid.clone(), goal.clone(), &Settings { jobs, ..settings.clone() });
+            assert!(parallel.resolve());
+            assert_eq!(parallel.get_total_cost(), FOUR_BY_FOUR_COST, "{} threads", jobs);
+            assert_eq!(parallel.get_steps().last(), Some(&goal));
+            assert_eq!(parallel.get_moves().len() as u32, FOUR_BY_FOUR_COST);
+            assert!(parallel.get_steps().windows(2).all(|w| w[0].get_move_to(&w[1]).is_some()));
+        }
+    }
+
+    #[test]
+    fn ara_star()
+    {
+        let (grid, goal) = four_by_four();
+        let settings = Settings { a_type: AType::AraStar, max_weight: Some(5), ..Settings::default() };
+        let mut algo = Algo::with_settings(grid.clone(), goal.clone(), &settings);
+        let mut streamed = Vec::new();
+        assert!(algo.resolve_with(|i| streamed.push(i.clone())));
+        assert_eq!(algo.get_total_cost(), FOUR_BY_FOUR_COST);
+        assert_eq!(algo.get_steps().last(), Some(&goal));
+        // Each improvement is handed over as it is found, the first one with the max weight, and
+        // every later one is cheaper or better bounded with a lower weight, down to an optimal one.
+        let improvements = algo.get_improvements();
+        assert_eq!(streamed, improvements);
+        assert_eq!(improvements[0].weight, 5);
+        assert!(improvements.windows(2).all(|w| w[1].weight < w[0].weight && (w[1].cost < w[0].cost || w[1].bound < w[0].bound)));
+        assert!(improvements.iter().all(|i| i.cost as f64 <= FOUR_BY_FOUR_COST as f64 * i.bound));
+        assert_eq!(improvements.last().map(|i| (i.cost, i.bound)), Some((FOUR_BY_FOUR_COST, 1.0)));
+
+        // Out of time at once: the first solution is still given.
+        let mut algo = Algo::with_settings(grid, goal, &Settings { time_limit: Some(Duration::ZERO), ..settings });
+        assert!(algo.resolve());
+        assert_eq!(algo.get_improvements().len(), 1);
+        assert_eq!(algo.get_improvements()[0].weight, 5);
+        assert!(algo.get_improvements()[0].bound >= 1.0);
+    }
+
+    #[test]
+    fn weighting_policies()
+    {
+        let (grid, goal) = four_by_four();
+        for a_type in [AType::AStar, AType::IDAStar]
+        {
+            for w_type in [WType::Constant, WType::Progressive, WType::Dynamic, WType::PiecewiseLinear]
+            {
+                let settings = Settings { a_type, w_type, max_weight: Some(3), ..Settings::default() };
+                let mut algo = Algo::with_settings(grid.clone(), goal.clone(), &settings);
+                assert!(algo.resolve());
+                // The fixed policies keep the max weight, the progressive one only reports the weight it reached.
+                let weight = algo.get_weight();
+                if w_type == WType::Progressive
+                {
+                    assert!((1..=3).contains(&weight), "{} {}", a_type, w_type);
+                }
+                else {
+                    assert_eq!(weight, 3, "{} {}", a_type, w_type);
+                }
+                assert_eq!(algo.get_bound(), Some(weight as f64), "{} {}", a_type, w_type);
+                assert!(algo.get_total_cost() <= FOUR_BY_FOUR_COST * weight, "{} {}", a_type, w_type);
+                let optimal = Settings { max_weight: Some(1), ..settings };
+                let mut algo = Algo::with_settings(grid.clone(), goal.clone(), &optimal);
+                assert!(algo.resolve());
+                assert_eq!(algo.get_total_cost(), FOUR_BY_FOUR_COST);
+                assert_eq!(algo.get_bound(), Some(1.0));
+            }
+        }
+        // Greedy search gives no guarantee, but BFS ignores the heuristic and stays exact with unit costs.
+        let grid = Grid::new(vec!(1, 2, 3, 7, 8, 4, 0, 6, 5), 3);
+        for (a_type, bound) in [(AType::AStar, None), (AType::Bfs, Some(1.0))]
+        {
+            let settings = Settings { a_type, greedy: true, ..Settings::default() };
+            let mut algo = Algo::with_settings(grid.clone(), grid.snail_goal(), &settings);
+            assert!(algo.resolve());
+            assert_eq!(algo.get_bound(), bound, "{}", a_type);
+        }
+    }
+
+    #[test]
+    fn algo_is_send()
+    {
+        fn assert_send<T: Send>() {}
+        assert_send::<super::Algo>();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_improvement()
+    {
+        let improvement = super::Improvement { cost: 24, weight: 3, bound: 1.5, elapsed: std::time::Duration::from_millis(12) };
+        let back: super::Improvement = serde_json::from_str(&serde_json::to_string(&improvement).unwrap()).unwrap();
+        assert_eq!(back, improvement);
+    }
 
     #[test]
     fn test_binary_heap_sort()
@@ -0,0 +1,135 @@
+use std::{
+    io::{self, Write},
+    time::Duration,
+};
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEventKind},
+    queue,
+    style::Print,
+};
+use n_puzzle::{grid::Grid, error::Error};
+use crate::tui::{self, RawTerminal};
+
+// Where the replay stands, kept apart from the terminal.
+struct Replay<'a>
+{
+    steps: &'a [Grid],
+    current: usize,
+    paused: bool
+}
+
+impl<'a> Replay<'a>
+{
+    fn new(steps: &'a [Grid]) -> Self
+    {
+        Replay { steps, current: 0, paused: false }
+    }
+
+    fn at_end(&self) -> bool
+    {
+        self.current + 1 >= self.steps.len()
+    }
+
+    fn forward(&mut self)
+    {
+        if !self.at_end()
+        {
+            self.current += 1;
+        }
+    }
+
+    fn back(&mut self)
+    {
+        self.current = self.current.saturating_sub(1);
+    }
+
+    // Tile which moved to reach the current step.
+    fn moved_tile(&self) -> Option<u16>
+    {
+        let previous = self.steps.get(self.current.checked_sub(1)?)?;
+        previous.get_moved_tile(&self.steps[self.current])
+    }
+}
+
+fn draw(out: &mut impl Write, replay: &Replay) -> io::Result<()>
+{
+    tui::draw_grid(out, &replay.steps[replay.current], replay.moved_tile())?;
+    let state = if replay.at_end() { " (end)" } else if replay.paused { " (paused)" } else { "" };
+    queue!(out, Print(format!("Step {}/{}{}\r\n\r\n", replay.current, replay.steps.len() - 1, state)))?;
+    queue!(out, Print("Space to pause or replay, left/right to step back/forward, q to quit.\r\n"))?;
+    out.flush()
+}
+
+fn run(steps: &[Grid], delay: Duration) -> io::Result<()>
+{
+    let _terminal = RawTerminal::enter()?;
+    let mut out = io::stdout();
+    let mut replay = Replay::new(steps);
+
+    loop
+    {
+        draw(&mut out, &replay)?;
+        // Waiting for a key is also what paces the animation.
+        if !replay.paused && !replay.at_end() && !event::poll(delay)?
+        {
+            replay.forward();
+            continue;
+        }
+        let key = match event::read()?
+        {
+            Event::Key(key) if key.kind == KeyEventKind::Press => key,
+            _ => continue
+        };
+        match key.code
+        {
+            KeyCode::Char('q') | KeyCode::Char('Q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Char(' ') if replay.at_end() => {
+                replay.current = 0;
+                replay.paused = false;
+            },
+            KeyCode::Char(' ') => replay.paused = !replay.paused,
+            KeyCode::Right | KeyCode::Char('l') => {
+                replay.paused = true;
+                replay.forward();
+            },
+            KeyCode::Left | KeyCode::Char('h') => {
+                replay.paused = true;
+                replay.back();
+            },
+            _ => ()
+        }
+    }
+}
+
+// Replays a solution in place, one step every `delay`.
+pub fn animate(steps: &[Grid], delay: Duration) -> Result<(), Error>
+{
+    if steps.is_empty()
+    {
+        return Ok(());
+    }
+    run(steps, delay).map_err(|e| Error::io("terminal", e))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use n_puzzle::grid::Move;
+
+    #[test]
+    fn replay()
+    {
+        let first = Grid::new(vec!(1, 2, 3, 8, 4, 0, 7, 6, 5), 3);
+        let steps = vec![first.clone(), first.move_zero(Move::Left).unwrap()];
+        let mut replay = Replay::new(&steps);
+        assert_eq!(replay.moved_tile(), None);
+        replay.back();
+        assert_eq!(replay.current, 0);
+        replay.forward();
+        assert_eq!(replay.moved_tile(), Some(4));
+        assert!(replay.at_end());
+        replay.forward();
+        assert_eq!(replay.current, 1);
+    }
+}
@@ -0,0 +1,378 @@
+use std::{
+    fmt,
+    fs,
+    io,
+    path::Path,
+    time::{Duration, Instant}
+};
+use crate::{
+    grid::Grid,
+    algo::{Algo, Settings},
+    error::Error,
+    parser::{parse_puzzle, InputFormat}
+};
+use serde_json::{json, Value};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Status
+{
+    Solved,
+    Unsolvable,
+    // The walls leave too many states to tell whether the puzzle is solvable.
+    Undecided,
+    // The uniform cost limit was reached before the goal.
+    LimitReached,
+    // The search ended without reaching the goal and without hitting the uniform cost limit.
+    NoSolution,
+    Invalid(String)
+}
+
+impl fmt::Display for Status
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self
+        {
+            Self::Solved => write!(f, "solved"),
+            Self::Unsolvable => write!(f, "unsolvable"),
+            Self::Undecided => write!(f, "undecided"),
+            Self::LimitReached => write!(f, "limit reached"),
+            Self::NoSolution => write!(f, "no solution"),
+            Self::Invalid(e) => write!(f, "invalid: {}", e)
+        }
+    }
+}
+
+// What we keep from solving one puzzle of a batch.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stats
+{
+    pub name: String,
+    pub size: Option<u8>,
+    pub cost: Option<u32>,
+    pub t_complex: u64,
+    pub s_complex: u64,
+    pub time: Duration,
+    pub status: Status
+}
+
+impl Stats
+{
+    fn new(name: String, size: Option<u8>, status: Status) -> Self
+    {
+        Stats
+        {
+            name,
+            size,
+            cost: None,
+            t_complex: 0,
+            s_complex: 0,
+            time: Duration::default(),
+            status
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum SummaryFormat
+{
+    #[default]
+    Csv,
+    Json
+}
+
+impl SummaryFormat
+{
+    pub fn from_str_or_default(input: Option<&str>) -> Result<Self, String>
+    {
+        match input
+        {
+            None => Ok(Self::default()),
+            Some("csv") => Ok(Self::Csv),
+            Some("json") => Ok(Self::Json),
+            Some(s) => Err(format!("This summary format does not exist: {}", s))
+        }
+    }
+}
+
+// `goal` defaults to the snail.
+pub fn solve(name: String, grid: Grid, goal: Option<Grid>, settings: &Settings) -> Stats
+{
+    let grid = grid.with_topology(settings.topology);
+    let size = Some(grid.get_lines());
+    let goal = goal.map_or_else(|| grid.snail_goal(), |g| g.with_topology(settings.topology));
+    match grid.solvable_to(&goal)
+    {
+        Some(false) => return Stats::new(name, size, Status::Unsolvable),
+        None => return Stats::new(name, size, Status::Undecided),
+        Some(true) => ()
+    }
+    if let Err(e) = settings.c_type.check_tiles(&grid)
+    {
+        return Stats::new(name, size, Status::Invalid(e));
+    }
+    let mut algo = Algo::with_settings(grid, goal, settings);
+    let start = Instant::now();
+    let solved = algo.resolve();
+    let status = if solved
+    {
+        Status::Solved
+    }
+    else if algo.limit_reached()
+    {
+        Status::LimitReached
+    }
+    else {
+        Status::NoSolution
+    };
+    Stats
+    {
+        name,
+        size,
+        cost: if solved { Some(algo.get_total_cost()) } else { None },
+        t_complex: algo.get_t_complex(),
+        s_complex: algo.get_s_complex(),
+        time: start.elapsed(),
+        status
+    }
+}
+
+pub fn solve_content(name: String, content: String, format: InputFormat, settings: &Settings) -> Stats
+{
+    match parse_puzzle(content, format)
+    {
+        Ok((grid, goal)) => solve(name, grid, goal, settings),
+        Err(e) => Stats::new(name, None, Status::Invalid(e.to_string()))
+    }
+}
+
+pub fn solve_all(puzzles: Vec<(String, String)>, format: InputFormat, settings: &Settings, jobs: usize) -> Vec<Stats>
+{
+    utils::run_parallel(puzzles, jobs, |(name, content)| solve_content(name, content, format, settings))
+}
+
+// Puzzles of a multi-puzzle file are read one after the other: a size line then as many rows.
+// Comments, blank lines and `---` marker lines between them are ignored. Whatever can't be split
+// this way is returned as a last chunk so the parser reports what is wrong with it.
+pub fn split_puzzles(content: &str) -> Vec<String>
+{
+    let lines: Vec<String> = utils::remove_comment_by_line(content, "#").into_iter().filter(|l| l != "---").collect();
+    let mut ret: Vec<String> = Vec::new();
+    let mut i = 0;
+
+    while i < lines.len()
+    {
+        match lines[i].parse::<usize>()
+        {
+            Ok(size) if i + size < lines.len() => {
+                ret.push(lines[i..=i + size].join("\n"));
+                i += size + 1;
+            },
+            _ => {
+                ret.push(lines[i..].join("\n"));
+                break;
+            }
+        }
+    }
+    ret
+}
+
+// Single-line files hold one puzzle per line, CSV and JSON files a single puzzle.
+fn split_content(content: &str, format: InputFormat) -> Vec<String>
+{
+    match format.detect(content)
+    {
+        InputFormat::Line => utils::remove_comment_by_line(content, "#"),
+        InputFormat::Csv | InputFormat::Json => vec![String::from(content)],
+        InputFormat::School | InputFormat::Auto => split_puzzles(content)
+    }
+}
+
+// Only `*` and `?` are supported, they never match a path separator.
+fn glob_match(pattern: &[char], name: &[char]) -> bool
+{
+    match (pattern.first(), name.first())
+    {
+        (None, None) => true,
+        (Some('*'), _) => glob_match(&pattern[1..], name) || (!name.is_empty() && glob_match(pattern, &name[1..])),
+        (Some('?'), Some(_)) => glob_match(&pattern[1..], &name[1..]),
+        (Some(p), Some(n)) if p == n => glob_match(&pattern[1..], &name[1..]),
+        _ => false
+    }
+}
+
+fn list_files(dir: &Path, pattern: Option<&str>) -> Result<Vec<String>, Error>
+{
+    let pattern: Option<Vec<char>> = pattern.map(|p| p.chars().collect());
+    let mut files: Vec<String> = fs::read_dir(dir).map_err(|e| Error::io(&dir.to_string_lossy(), e))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file())
+        .filter(|path| match (&pattern, path.file_name())
+        {
+            (None, _) => true,
+            (Some(p), Some(name)) => glob_match(p, &name.to_string_lossy().chars().collect::<Vec<char>>()),
+            _ => false
+        })
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+// Every puzzle found at `path` (a directory, a glob on file names or a file) with a name for each,
+// files holding several puzzles get their index appended to their name.
+pub fn collect_puzzles(path: &str, format: InputFormat) -> Result<Vec<(String, String)>, Error>
+{
+    let as_path = Path::new(path);
+    let files = if as_path.is_dir()
+    {
+        list_files(as_path, None)?
+    }
+    else if as_path.is_file()
+    {
+        vec![String::from(path)]
+    }
+    else {
+        let pattern = as_path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        if !pattern.contains(['*', '?'])
+        {
+            return Err(Error::io(path, io::Error::new(io::ErrorKind::NotFound, "Path is invalid/does not exist.")));
+        }
+        let dir = match as_path.parent()
+        {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new(".")
+        };
+        list_files(dir, Some(&pattern))?
+    };
+
+    let mut ret: Vec<(String, String)> = Vec::new();
+    for file in files
+    {
+        let content = fs::read_to_string(&file).map_err(|e| Error::io(&file, e))?;
+        let puzzles = split_content(&content, format);
+        if puzzles.len() == 1
+        {
+            ret.push((file, puzzles.into_iter().next().unwrap()));
+        }
+        else {
+            ret.extend(puzzles.into_iter().enumerate().map(|(i, p)| (format!("{}:{}", file, i + 1), p)));
+        }
+    }
+    Ok(ret)
+}
+
+fn csv_field(field: &str) -> String
+{
+    if field.contains([',', '"', '\n'])
+    {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    }
+    else {
+        String::from(field)
+    }
+}
+
+fn or_empty<T: fmt::Display>(value: Option<T>) -> String
+{
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+pub fn write_summary(stats: &[Stats], format: SummaryFormat) -> String
+{
+    match format
+    {
+        SummaryFormat::Csv => {
+            let mut ret = String::from("file,size,cost,nodes_expanded,max_nodes_in_memory,time_ms,status\n");
+            for s in stats
+            {
+                ret += &format!("{},{},{},{},{},{:.3},{}\n", csv_field(&s.name), or_empty(s.size), or_empty(s.cost),
+                    s.t_complex, s.s_complex, s.time.as_secs_f64() * 1000.0, csv_field(&s.status.to_string()));
+            }
+            ret
+        },
+        SummaryFormat::Json => {
+            let rows = stats.iter().map(|s| json!({
+                "file": s.name,
+                "size": s.size,
+                "cost": s.cost,
+                "nodes_expanded": s.t_complex,
+                "max_nodes_in_memory": s.s_complex,
+                "time_ms": s.time.as_micros() as f64 / 1000.0,
+                "status": s.status.to_string()
+            })).collect();
+            format!("{:#}\n", Value::Array(rows))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::cost::CType;
+
+    #[test]
+    fn split()
+    {
+        let content = "# first\n3\n1 2 3\n8 0 4\n7 6 5\n\n---\n# second\n3\n1 2 3\n8 4 0\n7 6 5\n";
+        let puzzles = split_puzzles(content);
+        assert_eq!(puzzles, vec!["3\n1 2 3\n8 0 4\n7 6 5", "3\n1 2 3\n8 4 0\n7 6 5"]);
+        assert_eq!(split_puzzles("3\n1 2 3\n"), vec!["3\n1 2 3"]);
+        assert_eq!(split_puzzles("a\n1 2 3\n"), vec!["a\n1 2 3"]);
+        assert_eq!(split_content("3: 1 2 3 8 0 4 7 6 5\n# second\n3: 1 2 3 8 4 0 7 6 5\n", InputFormat::Auto).len(), 2);
+        assert_eq!(split_content("1,2,3\n8,0,4\n7,6,5\n", InputFormat::Auto).len(), 1);
+    }
+
+    #[test]
+    fn glob()
+    {
+        let m = |p: &str, n: &str| glob_match(&p.chars().collect::<Vec<char>>(), &n.chars().collect::<Vec<char>>());
+        assert!(m("*.txt", "8-puzzle.txt"));
+        assert!(m("?-puzzle*", "8-puzzle.txt"));
+        assert!(m("*", ""));
+        assert!(!m("*.txt", "8-puzzle.txt.bak"));
+        assert!(!m("?-puzzle.txt", "15-puzzle.txt"));
+    }
+
+    #[test]
+    fn solve_batch()
+    {
+        let settings = Settings::default();
+        let auto = InputFormat::Auto;
+        let solved = solve_content(String::from("a"), String::from("3\n1 2 3\n8 4 0\n7 6 5"), auto, &settings);
+        assert_eq!(solved.status, Status::Solved);
+        assert_eq!(solved.cost, Some(1));
+        let unsolvable = solve_content(String::from("b"), String::from("3\n2 1 3\n8 0 4\n7 6 5"), auto, &settings);
+        assert_eq!(unsolvable.status, Status::Unsolvable);
+        let invalid = solve_content(String::from("c"), String::from("3\n1 2 3"), auto, &settings);
+        assert!(matches!(invalid.status, Status::Invalid(_)));
+        let table = Settings { c_type: CType::from_table("9 2").unwrap(), ..Settings::default() };
+        let off_board = solve_content(String::from("d"), String::from("3\n1 2 3\n8 4 0\n7 6 5"), auto, &table);
+        assert!(matches!(off_board.status, Status::Invalid(_)));
+
+        let csv = write_summary(&[solved.clone(), invalid.clone()], SummaryFormat::Csv);
+        assert!(csv.lines().nth(1).unwrap().starts_with("a,3,1,"));
+        assert!(csv.lines().nth(2).unwrap().starts_with("c,,,0,0,"));
+        let json = write_summary(&[solved, invalid], SummaryFormat::Json);
+        let rows: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(rows[0]["file"], "a");
+        assert_eq!(rows[0]["size"], 3);
+        assert_eq!(rows[0]["cost"], 1);
+        assert_eq!(rows[1]["size"], Value::Null);
+        assert_eq!(rows[1]["cost"], Value::Null);
+        assert!(json.find("\"file\"").unwrap() < json.find("\"status\"").unwrap());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_stats()
+    {
+        let stats = solve_content(String::from("a"), String::from("3\n1 2 3\n8 4 0\n7 6 5"), InputFormat::Auto, &Settings::default());
+        let back: Stats = serde_json::from_str(&serde_json::to_string(&stats).unwrap()).unwrap();
+        assert_eq!(back.status, Status::Solved);
+        assert_eq!(back.cost, stats.cost);
+        assert_eq!(back.time, stats.time);
+    }
+}
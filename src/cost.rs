@@ -0,0 +1,143 @@
+use std::fmt;
+use crate::grid::{Grid, WALL};
+
+// Costs go into the heuristics of the whole board and are weighted on top, higher ones would
+// overflow them.
+pub const MAX_COST: u32 = 1000;
+
+// How much it costs to slide a given tile into the blank.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum CType
+{
+    #[default]
+    Unit,
+    TileValue,
+    // Cost of each tile indexed by its value, tiles missing from the table cost 1.
+    Table(Vec<u32>)
+}
+
+impl fmt::Display for CType
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self
+        {
+            Self::Unit => write!(f, "Unit"),
+            Self::TileValue => write!(f, "Tile value"),
+            Self::Table(_) => write!(f, "Cost table")
+        }
+    }
+}
+
+impl CType
+{
+    pub fn from_str_or_default(input: Option<&str>) -> Result<Self, String>
+    {
+        match input
+        {
+            None => Ok(Self::default()),
+            Some("unit") => Ok(Self::Unit),
+            Some("tile_value") => Ok(Self::TileValue),
+            Some(c) => Err(format!("This cost function does not exist: {}", c))
+        }
+    }
+
+    // The table is made of `<tile> <cost>` lines, `#` starts a comment.
+    pub fn from_table(content: &str) -> Result<Self, String>
+    {
+        let mut table: Vec<u32> = Vec::new();
+        for line in utils::remove_comment_by_line(content, "#")
+        {
+            let pair: Vec<&str> = line.split_whitespace().collect();
+            let (tile, cost) = match pair.as_slice()
+            {
+                [tile, cost] => match (tile.parse::<u16>(), cost.parse::<u32>())
+                {
+                    (Ok(tile), Ok(cost)) => (tile, cost),
+                    (Err(_), _) if tile.parse::<u64>().is_ok() => return Err(format!("There is no tile {} on any board", tile)),
+                    _ => return Err(format!("Invalid token in cost table line: {}", line))
+                },
+                _ => return Err(format!("Cost table lines must be `<tile> <cost>`: {}", line))
+            };
+            // A free move would let the heuristic reach 0 before the goal is reached.
+            if cost == 0
+            {
+                return Err(format!("Moving tile {} must cost at least 1", tile));
+            }
+            if cost > MAX_COST
+            {
+                return Err(format!("Moving tile {} must cost at most {}", tile, MAX_COST));
+            }
+            // The blank is what moves, it never slides into itself.
+            if tile == 0 || tile == WALL
+            {
+                return Err(format!("There is no tile {} to move", tile));
+            }
+            let tile = tile as usize;
+            if table.len() <= tile
+            {
+                table.resize(tile + 1, 1);
+            }
+            table[tile] = cost;
+        }
+        Ok(Self::Table(table))
+    }
+
+    // A table naming a tile the grid doesn't have was written for another board.
+    pub fn check_tiles(&self, grid: &Grid) -> Result<(), String>
+    {
+        let last = grid.get_map().iter().copied().filter(|&t| t != WALL).max().unwrap_or(0) as usize;
+        match self
+        {
+            Self::Table(table) if table.len() > last + 1 => Err(format!("The cost table names tile {} which is not on the board", table.len() - 1)),
+            _ => Ok(())
+        }
+    }
+
+    pub fn of(&self, tile: u16) -> u32
+    {
+        match self
+        {
+            Self::Unit => 1,
+            Self::TileValue => u32::from(tile),
+            Self::Table(table) => table.get(tile as usize).copied().unwrap_or(1)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn cost_of()
+    {
+        assert_eq!(CType::Unit.of(7), 1);
+        assert_eq!(CType::TileValue.of(7), 7);
+        assert_eq!(CType::Table(vec!(1, 4, 2)).of(1), 4);
+        assert_eq!(CType::Table(vec!(1, 4, 2)).of(7), 1);
+    }
+
+    #[test]
+    fn table()
+    {
+        let table = CType::from_table("# tile cost\n1 4\n3 2 # comment\n").unwrap();
+        assert_eq!(table, CType::Table(vec!(1, 4, 1, 2)));
+        assert!(CType::from_table("1 0").is_err());
+        assert!(CType::from_table("1 2 3").is_err());
+        assert!(CType::from_table("1 W").is_err());
+        assert!(CType::from_table("4000000000 2").is_err());
+        assert!(CType::from_table("0 2").is_err());
+        assert!(CType::from_table("1 4000000000").is_err());
+        assert!(CType::from_table(&format!("1 {}", MAX_COST)).is_ok());
+    }
+
+    #[test]
+    fn tiles_on_the_board()
+    {
+        let grid = Grid::new(vec!(1, 2, 3, 8, 0, 4, 7, 6, 5), 3);
+        assert_eq!(CType::from_table("8 3").unwrap().check_tiles(&grid), Ok(()));
+        assert!(CType::from_table("9 3").unwrap().check_tiles(&grid).is_err());
+        assert_eq!(CType::TileValue.check_tiles(&grid), Ok(()));
+    }
+}
@@ -0,0 +1,131 @@
+use std::fmt;
+use crate::grid::{Grid, WALL};
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum DisplayStyle
+{
+    // What `Display` for `Grid` prints: tab separated with a blank line between rows.
+    #[default]
+    Tabs,
+    // Aligned columns in a box-drawing border.
+    Boxed,
+    // Same as `Boxed`, tiles already on their goal cell are shown in green.
+    Color,
+    // `3: 1 2 3 8 0 4 7 6 5`, the single-line input format.
+    Line
+}
+
+impl fmt::Display for DisplayStyle
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self
+        {
+            Self::Tabs => write!(f, "Tabs"),
+            Self::Boxed => write!(f, "Boxed"),
+            Self::Color => write!(f, "Color"),
+            Self::Line => write!(f, "Single line")
+        }
+    }
+}
+
+impl DisplayStyle
+{
+    pub fn from_str_or_default(input: Option<&str>) -> Result<Self, String>
+    {
+        match input
+        {
+            None => Ok(Self::default()),
+            Some("tabs") => Ok(Self::Tabs),
+            Some("boxed") => Ok(Self::Boxed),
+            Some("color") => Ok(Self::Color),
+            Some("line") => Ok(Self::Line),
+            Some(d) => Err(format!("This display style does not exist: {}", d))
+        }
+    }
+
+    // `goal` is only used to colour the tiles.
+    pub fn render(self, grid: &Grid, goal: &Grid) -> String
+    {
+        match self
+        {
+            Self::Tabs => grid.to_string(),
+            Self::Boxed => boxed(grid),
+            Self::Color => highlighted(grid, goal),
+            Self::Line => single_line(grid)
+        }
+    }
+}
+
+fn cell(tile: u16) -> String
+{
+    match tile
+    {
+        0 => String::from("_"),
+        WALL => String::from("X"),
+        n => n.to_string()
+    }
+}
+
+fn boxed_with(grid: &Grid, goal: Option<&Grid>) -> String
+{
+    let lines = grid.get_lines() as usize;
+    let width = (grid.get_map().len() - 1).to_string().len();
+    let border = "─".repeat(lines * (width + 1) + 1);
+    let mut ret = format!("┌{}┐\n", border);
+    for (r, row) in grid.get_map().chunks(lines).enumerate()
+    {
+        ret += "│";
+        for (c, &tile) in row.iter().enumerate()
+        {
+            let text = format!(" {:>width$}", cell(tile), width = width);
+            let in_place = tile != 0 && tile != WALL && goal.is_some_and(|g| g.get_map()[r * lines + c] == tile);
+            ret += &if in_place { format!("\x1b[32m{}\x1b[0m", text) } else { text };
+        }
+        ret += " │\n";
+    }
+    ret + &format!("└{}┘\n", border)
+}
+
+pub fn boxed(grid: &Grid) -> String
+{
+    boxed_with(grid, None)
+}
+
+// ANSI colours, for terminals only.
+pub fn highlighted(grid: &Grid, goal: &Grid) -> String
+{
+    boxed_with(grid, Some(goal))
+}
+
+pub fn single_line(grid: &Grid) -> String
+{
+    let tiles: Vec<String> = grid.get_map().iter().map(|&t| if t == WALL { String::from("X") } else { t.to_string() }).collect();
+    format!("{}: {}\n", grid.get_lines(), tiles.join(" "))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::parser::{parse_puzzle, InputFormat};
+
+    #[test]
+    fn renderers()
+    {
+        let grid = Grid::new(vec!(1, 2, 3, 8, 0, WALL, 7, 6, 5), 3);
+        assert_eq!(boxed(&grid), "┌───────┐\n│ 1 2 3 │\n│ 8 _ X │\n│ 7 6 5 │\n└───────┘\n");
+        assert_eq!(single_line(&grid), "3: 1 2 3 8 0 X 7 6 5\n");
+        let plain = Grid::new(vec!(1, 2, 3, 8, 0, 4, 7, 6, 5), 3);
+        assert_eq!(parse_puzzle(single_line(&plain), InputFormat::Auto).unwrap().0, plain);
+
+        let goal = Grid::new(vec!(1, 2, 3, 8, 4, WALL, 7, 0, 5), 3);
+        let colored = highlighted(&grid, &goal);
+        assert_eq!(colored.matches("\x1b[32m").count(), 6);
+        assert!(colored.contains("\x1b[32m 8\x1b[0m"));
+        assert!(!colored.contains("\x1b[32m 6"));
+
+        let big = Grid::new((0..16).collect(), 4);
+        assert!(boxed(&big).contains("│  _  1  2  3 │"));
+        assert_eq!(DisplayStyle::Tabs.render(&grid, &goal), grid.to_string());
+    }
+}
@@ -0,0 +1,120 @@
+use std::{fmt, io};
+use crate::parser::ParseError;
+
+// Everything that can make a run fail. Each kind has its own exit code so scripts can tell them
+// apart (when several puzzles are given, the code of the first one that failed is used):
+//   0  success
+//   1  invalid command line or option value
+//   2  a file could not be read or written
+//   3  the puzzle is malformed
+//   4  the puzzle is unsolvable
+//   5  the uniform cost limit (`-u`) was reached before the goal
+//   6  the search ended without reaching the goal
+//   7  no puzzle could be generated with the requested constraints
+//   8  the puzzle has walls and too many states to tell whether it is solvable
+#[derive(Debug)]
+pub enum Error
+{
+    Usage(String),
+    Io { path: String, source: io::Error },
+    Parse(ParseError),
+    Unsolvable,
+    LimitReached(u32),
+    NoSolution,
+    Generation(String),
+    // The walls break the parity rule and the reachable states are too many to search.
+    Undecided,
+    // Some of the puzzles given at once failed, `first` is the first of them.
+    Failed { failed: usize, total: usize, first: Box<Error> }
+}
+
+impl Error
+{
+    pub fn io(path: &str, source: io::Error) -> Self
+    {
+        Self::Io { path: String::from(path), source }
+    }
+
+    pub fn exit_code(&self) -> i32
+    {
+        match self
+        {
+            Self::Usage(_) => 1,
+            Self::Io { .. } => 2,
+            Self::Parse(_) => 3,
+            Self::Unsolvable => 4,
+            Self::LimitReached(_) => 5,
+            Self::NoSolution => 6,
+            Self::Generation(_) => 7,
+            Self::Undecided => 8,
+            Self::Failed { first, .. } => first.exit_code()
+        }
+    }
+}
+
+impl fmt::Display for Error
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self
+        {
+            Self::Usage(e) => write!(f, "{}", e),
+            Self::Io { path, source } => write!(f, "{}: {}", path, source),
+            Self::Parse(e) => write!(f, "{}", e),
+            Self::Unsolvable => write!(f, "Grid is unsolvable !"),
+            Self::LimitReached(g_max) => write!(f, "The uniform cost limit ({}) was reached before the goal", g_max),
+            Self::NoSolution => write!(f, "The search ended without reaching the goal"),
+            Self::Generation(e) => write!(f, "{}", e),
+            Self::Undecided => write!(f, "Cannot tell whether the grid is solvable, its walls leave too many states to search"),
+            Self::Failed { failed, total, .. } => write!(f, "{} of the {} puzzles could not be solved", failed, total)
+        }
+    }
+}
+
+impl std::error::Error for Error
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self
+        {
+            Self::Io { source, .. } => Some(source),
+            Self::Parse(e) => Some(e),
+            Self::Failed { first, .. } => Some(first.as_ref()),
+            _ => None
+        }
+    }
+}
+
+impl From<ParseError> for Error
+{
+    fn from(e: ParseError) -> Self
+    {
+        Self::Parse(e)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn distinct_exit_codes()
+    {
+        let errors = [
+            Error::Usage(String::new()),
+            Error::io("a", io::Error::from(io::ErrorKind::NotFound)),
+            Error::from(ParseError::Empty),
+            Error::Unsolvable,
+            Error::LimitReached(10),
+            Error::NoSolution,
+            Error::Generation(String::new()),
+            Error::Undecided
+        ];
+        let mut codes: Vec<i32> = errors.iter().map(Error::exit_code).collect();
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(codes.len(), errors.len());
+        assert!(!codes.contains(&0));
+        let failed = Error::Failed { failed: 2, total: 3, first: Box::new(Error::Unsolvable) };
+        assert_eq!(failed.exit_code(), Error::Unsolvable.exit_code());
+    }
+}
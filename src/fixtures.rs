@@ -0,0 +1,13 @@
+// Puzzles shared by the tests of several modules.
+use crate::{grid::Grid, puzzle_gen::create_snail_goal};
+
+// Optimal number of moves of `four_by_four` to the snail goal.
+pub const FOUR_BY_FOUR_COST: u32 = 20;
+
+// A 4x4 puzzle far enough from the goal to tell the solvers apart, yet quick to solve optimally.
+// Returns the puzzle and the snail goal.
+pub fn four_by_four() -> (Grid, Grid)
+{
+    let grid = Grid::new(vec!(12, 1, 15, 4, 11, 3, 2, 13, 14, 0, 8, 5, 10, 9, 7, 6), 4);
+    (grid, Grid::new(create_snail_goal(4), 4))
+}
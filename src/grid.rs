@@ -1,12 +1,28 @@
 use std::{
     fmt,
-    hash::{Hash, Hasher}
+    hash::{Hash, Hasher},
+    collections::{HashSet, VecDeque}
 };
-use utils::coord::Coord;
-use rand::{self, Rng};
-use crate::puzzle_gen::create_snail_goal;
+use utils::{coord::Coord, permutation};
+use rand::{Rng, seq::SliceRandom};
+use crate::{
+    puzzle_gen::{create_snail_goal, create_snail_goal_with},
+    cost::CType,
+    error::Error
+};
+#[cfg(feature = "serde")]
+use std::convert::TryFrom;
+#[cfg(feature = "serde")]
+use crate::parser::{check_map, ParseError};
+
+// Value stored in the map for a locked cell: the blank can never enter it and heuristics skip it.
+pub const WALL: u16 = u16::MAX;
+// Most states the reachability search of a grid with walls goes through before giving up.
+// Every 3x3 grid fits, bigger ones only when the walls leave few cells.
+pub const REACHABLE_STATES: usize = 100_000;
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Move
 {
     Up,
@@ -29,6 +45,17 @@ impl Move
         }
     }
 
+    pub fn opposite(self) -> Self
+    {
+        match self
+        {
+            Move::Up => Move::Down,
+            Move::Down => Move::Up,
+            Move::Right => Move::Left,
+            Move::Left => Move::Right
+        }
+    }
+
     pub fn apply(self, coord: &mut Coord)
     {
         match self
@@ -39,24 +66,84 @@ impl Move
             Move::Left => coord.dec_x()
         };
     }
+
+    // `lines` is needed to recognise moves which wrapped around an edge.
+    fn between(from: &Coord, to: &Coord, lines: u8) -> Option<Self>
+    {
+        let edge = lines as i16 - 1;
+        match (to.x - from.x, to.y - from.y)
+        {
+            (0, -1) => Some(Move::Up),
+            (0, 1) => Some(Move::Down),
+            (1, 0) => Some(Move::Right),
+            (-1, 0) => Some(Move::Left),
+            (0, d) if d == edge => Some(Move::Up),
+            (0, d) if d == -edge => Some(Move::Down),
+            (d, 0) if d == -edge => Some(Move::Right),
+            (d, 0) if d == edge => Some(Move::Left),
+            _ => None
+        }
+    }
 }
 
-#[derive(Copy, Clone, Debug)]
-pub enum HType
+impl fmt::Display for Move
 {
-    Hamming,
-    Manhattan,
-    LinearManhattan
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self
+        {
+            Self::Up => write!(f, "Up"),
+            Self::Down => write!(f, "Down"),
+            Self::Right => write!(f, "Right"),
+            Self::Left => write!(f, "Left")
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Topology
+{
+    #[default]
+    Flat,
+    // Moves wrap across the edges of the table.
+    Torus
+}
+
+impl fmt::Display for Topology
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self
+        {
+            Self::Flat => write!(f, "Flat"),
+            Self::Torus => write!(f, "Toroidal")
+        }
+    }
 }
 
-impl Default for HType
+impl Topology
 {
-    fn default() -> Self
+    pub fn from_str_or_default(input: Option<&str>) -> Result<Self, String>
     {
-        Self::LinearManhattan
+        match input
+        {
+            None => Ok(Self::default()),
+            Some("flat") => Ok(Self::Flat),
+            Some("torus") => Ok(Self::Torus),
+            Some(t) => Err(format!("This topology does not exist: {}", t))
+        }
     }
 }
 
+#[derive(Copy, Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HType
+{
+    Hamming,
+    Manhattan,
+    #[default]
+    LinearManhattan
+}
+
 impl fmt::Display for HType
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -84,12 +171,49 @@ impl HType
     }
 }
 
+// Only the map, size and topology are serialized, the blanks are found again from the map.
+// A deserialized grid goes through the same checks as a parsed one.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "GridData", into = "GridData"))]
 pub struct Grid
 {
     map: Vec<u16>,
-    z_pos: u16,
+    // Absolute position of every blank, a blank keeps its index in this list when it moves.
+    z_pos: Vec<u16>,
+    lines: u8,
+    topology: Topology,
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct GridData
+{
+    map: Vec<u16>,
     lines: u8,
+    #[serde(default)]
+    topology: Topology
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<GridData> for Grid
+{
+    type Error = ParseError;
+
+    fn try_from(data: GridData) -> Result<Self, ParseError>
+    {
+        check_map("map", &data.map, data.lines as usize)?;
+        Ok(Grid::new(data.map, data.lines).with_topology(data.topology))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<Grid> for GridData
+{
+    fn from(grid: Grid) -> Self
+    {
+        GridData { map: grid.map, lines: grid.lines, topology: grid.topology }
+    }
 }
 
 impl Hash for Grid
@@ -116,62 +240,226 @@ impl Grid
     {
         Self
         {
-            z_pos: map.iter().position(|&x| x == 0).unwrap_or(0) as u16,
+            z_pos: map.iter().enumerate().filter(|(_, &x)| x == 0).map(|(i, _)| i as u16).collect(),
             map,
-            lines
+            lines,
+            topology: Topology::default()
         }
     }
 
-    pub fn new_random(lines: u8) -> Self
+    pub fn with_topology(mut self, topology: Topology) -> Self
+    {
+        self.topology = topology;
+        self
+    }
+
+    pub fn new_random<R: Rng>(lines: u8, rng: &mut R) -> Self
+    {
+        Self::new_random_walk(lines, 512 * lines as u32, rng)
+    }
+
+    // Random walk of `moves` moves of the blank from the goal, it never undoes its previous move.
+    pub fn new_random_walk<R: Rng>(lines: u8, moves: u32, rng: &mut R) -> Self
     {
-        let mut rng = rand::thread_rng();
         let mut puzzle = Self::new(create_snail_goal(lines), lines);
-        for _ in 0..(512 * lines as u128)
+        let mut last: Option<Move> = None;
+        let mut done = 0;
+        while done < moves
         {
-            puzzle = puzzle.move_zero(Move::from(rng.gen_range(0..4))).unwrap_or(puzzle);
+            let mov = Move::from(rng.gen_range(0..4));
+            if last == Some(mov.opposite())
+            {
+                continue;
+            }
+            if let Some(next) = puzzle.move_zero(mov)
+            {
+                puzzle = next;
+                last = Some(mov);
+                done += 1;
+            }
         }
         puzzle
     }
 
+    // Every solvable state has the same probability: shuffle the tiles and, when the
+    // permutation has the wrong parity, swap two tiles which fixes it.
+    pub fn new_random_uniform<R: Rng>(lines: u8, rng: &mut R) -> Self
+    {
+        let mut map: Vec<u16> = (0..lines as u16 * lines as u16).collect();
+        map.shuffle(rng);
+        let puzzle = Self::new(map, lines);
+        if puzzle.solvable() == Some(true)
+        {
+            puzzle
+        }
+        else {
+            puzzle.swap_tiles()
+        }
+    }
+
+    // Swaps the first two tiles which are not a blank, this flips the solvability of a flat puzzle.
+    pub fn swap_tiles(&self) -> Self
+    {
+        let mut map = self.map.clone();
+        let tiles: Vec<usize> = map.iter().enumerate().filter(|(_, &x)| x != 0 && x != WALL).map(|(i, _)| i).take(2).collect();
+        if let [a, b] = tiles[..]
+        {
+            map.swap(a, b);
+        }
+        Self::new(map, self.lines).with_topology(self.topology)
+    }
+
     pub fn get_lines(&self) -> u8
     {
         self.lines
     }
 
+    pub fn get_map(&self) -> &[u16]
+    {
+        &self.map
+    }
+
+    // Writes the grid in the puzzle file format read by the parser, `#` comments included.
+    pub fn to_puzzle_file(&self, comments: &[String]) -> String
+    {
+        let width = (self.map.len() - 1).to_string().len();
+        let mut ret: String = comments.iter().map(|c| format!("# {}\n", c)).collect();
+        ret += &format!("{}\n", self.lines);
+        for row in self.map.chunks(self.lines as usize)
+        {
+            let row: Vec<String> = row.iter().map(|&x| match x
+            {
+                WALL => format!("{:>width$}", "X", width = width),
+                n => format!("{:>width$}", n, width = width)
+            }).collect();
+            ret += &row.join(" ");
+            ret += "\n";
+        }
+        ret
+    }
+
+    // Perfect hash of a grid without walls nor extra blanks, its map read as a permutation.
+    // `None` above 4x4, the rank would not fit in a u64.
+    pub fn rank(&self) -> Option<u64>
+    {
+        if self.lines > 4 || self.has_walls() || self.get_blanks() != 1
+        {
+            return None;
+        }
+        Some(permutation::rank(&self.map))
+    }
+
+    pub fn from_rank(rank: u64, lines: u8) -> Self
+    {
+        Self::new(permutation::unrank(rank, lines as usize * lines as usize), lines)
+    }
+
+    pub fn get_topology(&self) -> Topology
+    {
+        self.topology
+    }
+
+    pub fn get_blanks(&self) -> usize
+    {
+        self.z_pos.len()
+    }
+
+    pub fn has_walls(&self) -> bool
+    {
+        self.map.contains(&WALL)
+    }
+
+    pub fn get_walls(&self) -> Vec<u16>
+    {
+        self.map.iter().enumerate().filter(|(_, &x)| x == WALL).map(|(i, _)| i as u16).collect()
+    }
+
+    // Builds the snail goal matching this grid, walls stay where they are and are skipped by the snail.
+    pub fn snail_goal(&self) -> Self
+    {
+        let goal = if self.has_walls() || self.get_blanks() > 1
+        {
+            Self::new(create_snail_goal_with(self.lines, &self.get_walls(), self.get_blanks() as u16), self.lines)
+        }
+        else {
+            Self::new(create_snail_goal(self.lines), self.lines)
+        };
+        goal.with_topology(self.topology)
+    }
+
     pub fn move_zero(&self, mov: Move) -> Option<Self>
     {
-        let mut z_pos = Coord::from_abs(self.z_pos as u32, self.lines);
+        self.move_blank(0, mov)
+    }
+
+    pub fn move_blank(&self, blank: usize, mov: Move) -> Option<Self>
+    {
+        let from = *self.z_pos.get(blank)?;
+        let mut z_pos = Coord::from_abs(from as u32, self.lines);
         let mut map = self.map.clone();
 
         mov.apply(&mut z_pos);
+        if self.topology == Topology::Torus
+        {
+            z_pos.wrap(self.lines);
+        }
         if z_pos.is_out_of_table(self.lines)
         {
             return None;
         }
 
-        let z_pos = z_pos.to_abs(self.lines);
-        map[self.z_pos as usize] = map[z_pos as usize];
-        map[z_pos as usize] = 0;
+        let to = z_pos.to_abs(self.lines) as usize;
+        // Swapping two blanks (or moving into a wall) does not change anything.
+        if map[to] == WALL || map[to] == 0
+        {
+            return None;
+        }
+        map[from as usize] = map[to];
+        map[to] = 0;
+        let mut z_pos = self.z_pos.clone();
+        z_pos[blank] = to as u16;
         Some(Self
         {
             map,
             lines: self.lines,
-            z_pos: z_pos as u16
+            z_pos,
+            topology: self.topology
         })
     }
 
     pub fn move_all_possible(&self) -> Vec<Self>
     {
-        [Move::Up, Move::Down, Move::Right, Move::Left].iter().filter_map(|&m| self.move_zero(m)).collect()
+        (0..self.z_pos.len()).flat_map(|b| [Move::Up, Move::Down, Move::Right, Move::Left].iter().filter_map(move |&m| self.move_blank(b, m))).collect()
+    }
+
+    // Value of the tile which slid into a blank to go from `self` to `next`.
+    pub fn get_moved_tile(&self, next: &Grid) -> Option<u16>
+    {
+        let (blank, _) = self.get_move_to(next)?;
+        Some(self.map[next.z_pos[blank] as usize])
+    }
+
+    // Returns which blank moved and in which direction to go from `self` to `next`,
+    // `next` must have been produced by `move_blank` (or `move_all_possible`) on `self`.
+    pub fn get_move_to(&self, next: &Grid) -> Option<(usize, Move)>
+    {
+        let blank = self.z_pos.iter().zip(next.z_pos.iter()).position(|(a, b)| a != b)?;
+        Move::between(&Coord::from_abs(self.z_pos[blank] as u32, self.lines), &Coord::from_abs(next.z_pos[blank] as u32, self.lines), self.lines).map(|m| (blank, m))
     }
 
     pub fn hamming(&self, goal: &Grid) -> u32
     {
-        self.map.iter().zip(goal.map.iter()).filter(|(i, _)| **i != 0).fold(0, |acc, (i, g)| 
+        self.hamming_with_cost(goal, &CType::Unit)
+    }
+
+    // Every misplaced tile has to move at least once, so it costs at least its own move cost.
+    pub fn hamming_with_cost(&self, goal: &Grid, cost: &CType) -> u32
+    {
+        self.map.iter().zip(goal.map.iter()).filter(|(i, _)| **i != 0 && **i != WALL).fold(0, |acc, (i, g)| 
         {
             if i != g 
             {
-                acc + 1
+                acc.saturating_add(cost.of(*i))
             }
             else {
                 acc
@@ -179,8 +467,22 @@ impl Grid
         })
     }
 
-    pub fn solvable(&self) -> bool
+    // `None` when the grid has walls and is too big to tell, see `walled_solvable_to`.
+    pub fn solvable(&self) -> Option<bool>
     {
+        if self.has_walls()
+        {
+            return self.walled_solvable_to(&self.snail_goal());
+        }
+        // With two blanks or more any two tiles can be swapped, so every arrangement is solvable.
+        else if self.get_blanks() > 1
+        {
+            return Some(true);
+        }
+        else if self.topology == Topology::Torus
+        {
+            return Some(self.toroidal_solvable());
+        }
         let mut solve = self.map.clone();
         let mut inv_cout: u16 = 0;
         let ret: bool;
@@ -202,37 +504,217 @@ impl Grid
             }
         }
         
-        if self.lines % 2 == 0
+        if self.lines.is_multiple_of(2)
         {
             if blank_bot % 2 == 0
             {
-                ret = inv_cout % 2 != 0;
+                ret = !inv_cout.is_multiple_of(2);
             }
             else {
-                ret = inv_cout % 2 == 0;
+                ret = inv_cout.is_multiple_of(2);
             }
         }
         else {
-            ret = inv_cout % 2 != 0;
+            ret = !inv_cout.is_multiple_of(2);
         }
     if self.lines < 6
     {
-        ret
+        Some(ret)
     }
     else {
-        !ret
+        Some(!ret)
+    }
+    }
+
+    // On an odd sized torus the blank can go around a row and come back with an odd permutation,
+    // so everything is solvable. On an even sized one the table is still a checkerboard and the
+    // parity of the permutation must match the parity of the blank's distance to its goal cell.
+    fn toroidal_solvable(&self) -> bool
+    {
+        !self.lines.is_multiple_of(2) || self.parity_matches(&self.snail_goal())
     }
+
+    // Every move swaps the blank with a tile and moves the blank by one cell (or by an odd number
+    // of cells when wrapping around an even sized torus), so with a single blank the parity of the
+    // permutation to `goal` always matches the parity of the blank's distance to its goal cell.
+    fn parity_matches(&self, goal: &Grid) -> bool
+    {
+        // Walls stay in place, several of them would all map to the first one otherwise.
+        let mut perm: Vec<usize> = self.map.iter().enumerate()
+            .map(|(i, t)| if *t == WALL { i } else { goal.map.iter().position(|g| g == t).unwrap_or(0) }).collect();
+        let mut swaps = 0;
+        for i in 0..perm.len()
+        {
+            while perm[i] != i
+            {
+                let j = perm[i];
+                perm.swap(i, j);
+                swaps += 1;
+            }
+        }
+        let blank = Coord::from_abs(self.z_pos[0] as u32, self.lines);
+        let goal_blank = Coord::from_abs(goal.z_pos[0] as u32, self.lines);
+        swaps % 2 == blank.manhattan(&goal_blank) % 2
+    }
+
+    // Same as `solvable` towards any goal holding the same tiles and walls.
+    pub fn solvable_to(&self, goal: &Grid) -> Option<bool>
+    {
+        if *goal == self.snail_goal()
+        {
+            self.solvable()
+        }
+        else if self.has_walls()
+        {
+            self.walled_solvable_to(goal)
+        }
+        else if self.get_blanks() > 1 || (self.topology == Topology::Torus && !self.lines.is_multiple_of(2))
+        {
+            Some(true)
+        }
+        else {
+            Some(self.parity_matches(goal))
+        }
+    }
+
+    // `Error::Unsolvable`, or `Error::Undecided` when the walls leave too many states to tell.
+    pub fn check_solvable_to(&self, goal: &Grid) -> Result<(), Error>
+    {
+        match self.solvable_to(goal)
+        {
+            Some(true) => Ok(()),
+            Some(false) => Err(Error::Unsolvable),
+            None => Err(Error::Undecided)
+        }
+    }
+
+    // Walls break the inversion parity argument unless the cells left to the blank are well
+    // connected, otherwise the goal has to be searched for.
+    fn walled_solvable_to(&self, goal: &Grid) -> Option<bool>
+    {
+        if self.parity_applies()
+        {
+            Some(self.parity_matches(goal))
+        }
+        else {
+            self.reachable(goal)
+        }
+    }
+
+    // Wilson's theorem: a single blank moving through a 2-connected graph which is not a mere
+    // cycle reaches every arrangement of the right parity, flat grids being bipartite. So the parity
+    // rule still holds when no free cell splits the others in two and one of them has 3 neighbours.
+    fn parity_applies(&self) -> bool
+    {
+        let free = self.map.iter().filter(|&&t| t != WALL).count();
+        if self.topology != Topology::Flat || self.z_pos.len() != 1 || free < 3
+        {
+            return false;
+        }
+        let mut order = vec![0; self.map.len()];
+        let mut low = vec![0; self.map.len()];
+        let mut count = 0;
+        let cut = self.has_cut_cell(self.z_pos[0] as usize, None, &mut order, &mut low, &mut count);
+        let cycle = (0..self.map.len()).all(|c| self.map[c] == WALL || self.free_neighbours(c).len() < 3);
+        count == free && !cut && !cycle
+    }
+
+    // Cells next to `cell` on a flat grid which are not walls.
+    fn free_neighbours(&self, cell: usize) -> Vec<usize>
+    {
+        [Move::Up, Move::Down, Move::Right, Move::Left].iter().filter_map(|&m|
+        {
+            let mut coord = Coord::from_abs(cell as u32, self.lines);
+            m.apply(&mut coord);
+            if coord.is_out_of_table(self.lines)
+            {
+                return None;
+            }
+            let next = coord.to_abs(self.lines) as usize;
+            if self.map[next] == WALL { None } else { Some(next) }
+        }).collect()
+    }
+
+    // Depth-first search numbering the free cells in `order`, `low` is the smallest number a
+    // subtree reaches with one more step back. A cell whose subtree can't reach above it cuts the graph.
+    fn has_cut_cell(&self, cell: usize, parent: Option<usize>, order: &mut [usize], low: &mut [usize], count: &mut usize) -> bool
+    {
+        *count += 1;
+        order[cell] = *count;
+        low[cell] = *count;
+        let mut children = 0;
+        let mut cut = false;
+        for next in self.free_neighbours(cell)
+        {
+            if order[next] == 0
+            {
+                children += 1;
+                cut |= self.has_cut_cell(next, Some(cell), order, low, count);
+                low[cell] = low[cell].min(low[next]);
+                cut |= parent.is_some() && low[next] >= order[cell];
+            }
+            else if Some(next) != parent
+            {
+                low[cell] = low[cell].min(order[next]);
+            }
+        }
+        cut || (parent.is_none() && children > 1)
+    }
+
+    // Breadth-first search over the states reachable from this grid, `None` when the goal
+    // wasn't found within `REACHABLE_STATES` states.
+    pub fn reachable(&self, goal: &Grid) -> Option<bool>
+    {
+        let mut seen: HashSet<Grid> = HashSet::new();
+        let mut queue: VecDeque<Grid> = VecDeque::new();
+
+        seen.insert(self.clone());
+        queue.push_back(self.clone());
+        while let Some(grid) = queue.pop_front()
+        {
+            if grid == *goal
+            {
+                return Some(true);
+            }
+            for next in grid.move_all_possible()
+            {
+                if !seen.contains(&next)
+                {
+                    if seen.len() >= REACHABLE_STATES
+                    {
+                        return None;
+                    }
+                    seen.insert(next.clone());
+                    queue.push_back(next);
+                }
+            }
+        }
+        Some(false)
+    }
+
+    fn distance(&self, from: &Coord, to: &Coord) -> u32
+    {
+        match self.topology
+        {
+            Topology::Flat => from.manhattan(to),
+            Topology::Torus => from.toroidal_manhattan(to, self.lines)
+        }
     }
 
     pub fn manhattan(&self, goal: &Grid) -> u32
     {
-        self.map.iter().zip(goal.map.iter()).filter(|(i, _)| **i != 0).fold(0, |acc, (i, g)| 
+        self.manhattan_with_cost(goal, &CType::Unit)
+    }
+
+    pub fn manhattan_with_cost(&self, goal: &Grid, cost: &CType) -> u32
+    {
+        self.map.iter().zip(goal.map.iter()).filter(|(i, _)| **i != 0 && **i != WALL).fold(0, |acc, (i, g)| 
         {
             let goal_cord = Coord::from_abs(goal.map.iter().enumerate().find(|(_, y)| **y == *i).unwrap().0 as u32, self.lines);
             let self_cord = Coord::from_abs(self.map.iter().enumerate().find(|(_, y)| **y == *i).unwrap().0 as u32, self.lines);
             if i != g
             {
-                acc + ((goal_cord.x - self_cord.x).abs() as u32 + (goal_cord.y - self_cord.y).abs() as u32)
+                acc.saturating_add(self.distance(&self_cord, &goal_cord).saturating_mul(cost.of(*i)))
             }
             else {
                 acc
@@ -240,15 +722,15 @@ impl Grid
         })
     }
 
-    fn check_misplaced(from: &Grid, goal: &Grid) -> Vec<(Coord, Coord)>
+    fn check_misplaced(from: &Grid, goal: &Grid) -> Vec<(u16, Coord, Coord)>
     {
-        from.map.iter().zip(goal.map.iter()).filter(|(i, _)| **i != 0).filter_map(|(i, g)| 
+        from.map.iter().zip(goal.map.iter()).filter(|(i, _)| **i != 0 && **i != WALL).filter_map(|(i, g)| 
         {
             let goal_cord = Coord::from_abs(goal.map.iter().enumerate().find(|(_, y)| **y == *i).unwrap().0 as u32, from.lines);
             let self_cord = Coord::from_abs(from.map.iter().enumerate().find(|(_, y)| **y == *i).unwrap().0 as u32, from.lines);
             if i != g 
             {
-                Some((self_cord, goal_cord))
+                Some((*i, self_cord, goal_cord))
             }
             else {
                 None
@@ -258,16 +740,27 @@ impl Grid
 
     pub fn linear_conflict(&self, goal: &Grid) -> u32
     {
+        self.linear_conflict_with_cost(goal, &CType::Unit)
+    }
+
+    // One of the two tiles in conflict has to leave the line, we count the cheapest one.
+    pub fn linear_conflict_with_cost(&self, goal: &Grid, cost: &CType) -> u32
+    {
+        // Tiles can go around a row or a column the other way on a torus, a conflict does not cost anything.
+        if self.topology == Topology::Torus
+        {
+            return 0;
+        }
         let mut ret: u32 = 0;
-        let mut conflict: Vec<(Coord, Coord)> = Grid::check_misplaced(self, goal).into_iter().filter(|(from, goal)| from.x == goal.x || from.y == goal.y).rev().collect();
+        let mut conflict: Vec<(u16, Coord, Coord)> = Grid::check_misplaced(self, goal).into_iter().filter(|(_, from, goal)| from.x == goal.x || from.y == goal.y).rev().collect();
 
-        while let Some((f, g)) = conflict.pop()
+        while let Some((t, f, g)) = conflict.pop()
         {
-            for (_, p) in conflict.iter().filter(|(o, p)| (f.x == g.x && f.x == o.x && g.x == p.x) || (f.y == g.y && f.y == o.y && g.y == p.y))
+            for (u, _, p) in conflict.iter().filter(|(_, o, p)| (f.x == g.x && f.x == o.x && g.x == p.x) || (f.y == g.y && f.y == o.y && g.y == p.y))
             {
                 if (g.x - p.x) > 0 || (g.y - p.y) > 0
                 {
-                    ret += 1;
+                    ret = ret.saturating_add(cost.of(t).min(cost.of(*u)));
                 }
             }
         }
@@ -276,7 +769,12 @@ impl Grid
 
     pub fn linear_manhattan(&self, goal: &Grid) -> u32
     {
-        self.manhattan(goal) + self.linear_conflict(goal) * 2
+        self.linear_manhattan_with_cost(goal, &CType::Unit)
+    }
+
+    pub fn linear_manhattan_with_cost(&self, goal: &Grid, cost: &CType) -> u32
+    {
+        self.manhattan_with_cost(goal, cost).saturating_add(self.linear_conflict_with_cost(goal, cost).saturating_mul(2))
     }
 }
 
@@ -285,17 +783,17 @@ impl fmt::Display for Grid
     #[allow(clippy::unit_arg, clippy::let_unit_value)]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
     {
-        let len = self.map.len();
-        let col = (len as f32).sqrt() as u32;
+        let col = self.lines as usize;
         let mut result = ();
         for (i, x) in self.map.iter().enumerate()
         {
             result = match x
             {
                 0 => write!(f, "\t_")?,
+                &WALL => write!(f, "\tX")?,
                 n => write!(f, "\t{}", n)?
             };
-            if (i + 1) % col as usize == 0 && i != 0
+            if (i + 1) % col == 0
             {
                 result = write!(f, "\n\n")?;
             }
@@ -314,6 +812,7 @@ impl fmt::Display for Grid
 mod tests
 {
     use super::*;
+    use crate::{puzzle_gen::seeded_rng, fixtures::four_by_four};
 
     #[test]
     fn display()
@@ -356,15 +855,30 @@ mod tests
         assert_eq!(test.manhattan(&goal), expected);
     }
 
+    #[test]
+    fn heuristics_with_cost()
+    {
+        let goal = Grid::new(vec!(1, 2, 3, 8, 0, 4, 7, 6, 5), 3);
+        let test = Grid::new(vec!(3, 1, 2, 8, 0, 4, 7, 6, 5), 3);
+        assert_eq!(test.hamming_with_cost(&goal, &CType::TileValue), 6);
+        assert_eq!(test.manhattan_with_cost(&goal, &CType::TileValue), 1 + 2 + 3 * 2);
+        // Conflicts (3, 1) and (3, 2) each cost the cheapest tile twice.
+        assert_eq!(test.linear_conflict_with_cost(&goal, &CType::TileValue), 1 + 2);
+        assert_eq!(test.linear_manhattan_with_cost(&goal, &CType::TileValue), 9 + 2 * 3);
+        assert_eq!(test.linear_manhattan_with_cost(&goal, &CType::Unit), test.linear_manhattan(&goal));
+        let next = test.move_zero(Move::Left).unwrap();
+        assert_eq!(test.get_moved_tile(&next), Some(8));
+    }
+
     #[test]
     fn misplaced()
     {
         let goal = Grid::new(vec!(1, 2, 3, 8, 0, 4, 7, 6, 5), 3);
         let test = Grid::new(vec!(3, 1, 2, 8, 0, 4, 7, 6, 5), 3);
 
-        assert_eq!(Grid::check_misplaced(&test, &goal), vec![ (Coord::new(0, 0), Coord::new(2, 0) ), 
-                                                        (Coord::new(1, 0), Coord::new(0, 0) ), 
-                                                        (Coord::new(2, 0), Coord::new(1, 0) ) ]);
+        assert_eq!(Grid::check_misplaced(&test, &goal), vec![ (3, Coord::new(0, 0), Coord::new(2, 0) ), 
+                                                        (1, Coord::new(1, 0), Coord::new(0, 0) ), 
+                                                        (2, Coord::new(2, 0), Coord::new(1, 0) ) ]);
     }
 
     #[test]
@@ -403,39 +917,296 @@ mod tests
                                 0, 7, 6, 
                                 8, 2, 4, 
                                 1, 5, 3), 3);
-        println!("{}", test.solvable());
+        println!("{:?}", test.solvable());
         let test = Grid::new(vec!(
                                 0, 1, 3, 
                                 4, 8, 6, 
                                 2, 5, 7), 3);
-        println!("{}", test.solvable());
+        println!("{:?}", test.solvable());
         let test = Grid::new(vec!(
                                 2, 1, 0, 
                                 4, 6, 5, 
                                 8, 7, 3), 3);
-        println!("{}", test.solvable());
+        println!("{:?}", test.solvable());
         let test = Grid::new(vec!(
                                 2, 5, 0, 
                                 8, 7, 6, 
                                 3, 4, 1), 3);
-        println!("{}", test.solvable());
+        println!("{:?}", test.solvable());
         // Below is solvable
         let test = Grid::new(vec!(
                                 1, 0, 3, 
                                 8, 2, 4, 
                                 7, 6, 5), 3);
-        println!("{}", test.solvable());
+        println!("{:?}", test.solvable());
         let test = Grid::new(vec!(
                                 1, 2, 3, 
                                 8, 4, 0, 
                                 7, 6, 5), 3);
-        println!("{}", test.solvable());
+        println!("{:?}", test.solvable());
+    }
+
+    #[test]
+    fn move_zero_into_wall()
+    {
+        let test = Grid::new(vec!(
+                                1, 2, 3,
+                                8, 0, WALL,
+                                7, 6, 5), 3);
+        assert!(test.move_zero(Move::Right).is_none());
+        assert!(test.move_zero(Move::Left).is_some());
+        assert_eq!(test.move_all_possible().len(), 3);
+    }
+
+    #[test]
+    fn snail_goal_with_walls()
+    {
+        let test = Grid::new(vec!(
+                                1, 2, WALL,
+                                6, 0, 3,
+                                WALL, 5, 4), 3);
+        let expected = Grid::new(vec!(
+                                1, 2, WALL,
+                                6, 0, 3,
+                                WALL, 5, 4), 3);
+        assert_eq!(test.snail_goal(), expected);
+        let test = Grid::new(vec!(
+                                WALL, 1, 2,
+                                3, 4, 5,
+                                6, 0, 7), 3);
+        let expected = Grid::new(vec!(
+                                WALL, 1, 2,
+                                7, 0, 3,
+                                6, 5, 4), 3);
+        assert_eq!(test.snail_goal(), expected);
+    }
+
+    #[test]
+    fn heuristics_ignore_walls()
+    {
+        let goal = Grid::new(vec!(
+                                1, 2, WALL,
+                                6, 0, 3,
+                                WALL, 5, 4), 3);
+        let test = Grid::new(vec!(
+                                1, 2, WALL,
+                                6, 3, 0,
+                                WALL, 5, 4), 3);
+        assert_eq!(test.hamming(&goal), 1);
+        assert_eq!(test.manhattan(&goal), 1);
+        assert_eq!(test.linear_conflict(&goal), 0);
+    }
+
+    #[test]
+    fn solvable_with_walls()
+    {
+        let test = Grid::new(vec!(
+                                1, 2, WALL,
+                                6, 3, 0,
+                                WALL, 5, 4), 3);
+        assert_eq!(test.solvable(), Some(true));
+        // The wall in the middle turns the board into a ring, tiles can only rotate around it.
+        let test = Grid::new(vec!(
+                                1, 2, 3,
+                                7, WALL, 4,
+                                6, 5, 0), 3);
+        assert_eq!(test.solvable(), Some(true));
+        let test = Grid::new(vec!(
+                                2, 1, 3,
+                                7, WALL, 4,
+                                6, 5, 0), 3);
+        assert_eq!(test.solvable(), Some(false));
+        // A corner wall keeps the parity rule, no search is needed even on big grids.
+        let mut map: Vec<u16> = (0..24).collect();
+        map.push(WALL);
+        let test = Grid::new(map, 5).snail_goal().move_zero(Move::Up).unwrap().move_zero(Move::Left).unwrap();
+        assert!(test.parity_applies());
+        assert_eq!(test.solvable(), Some(true));
+        assert_eq!(test.swap_tiles().solvable(), Some(false));
+        // The parity rule agrees with the search wherever it applies.
+        let goal = Grid::new(vec!(1, 2, 3, 8, 0, 4, 7, 6, WALL), 3);
+        let mut grid = goal.clone();
+        for m in [Move::Up, Move::Left, Move::Down, Move::Down, Move::Right, Move::Up, Move::Left, Move::Up]
+        {
+            grid = grid.move_zero(m).unwrap_or(grid);
+            assert!(grid.parity_applies());
+            assert_eq!(grid.solvable_to(&goal), grid.reachable(&goal));
+            assert_eq!(grid.swap_tiles().solvable_to(&goal), grid.swap_tiles().reachable(&goal));
+        }
+        // A single gap in a row of walls cuts the grid in two, the search gives up.
+        let test = Grid::new(vec!(
+                                1, 2, 3, 4, 5,
+                                16, 17, 18, 19, 6,
+                                WALL, WALL, 0, WALL, WALL,
+                                14, 15, 20, 8, 7,
+                                13, 12, 11, 10, 9), 5);
+        assert!(!test.parity_applies());
+        assert_eq!(test.solvable(), None);
+        assert!(matches!(test.check_solvable_to(&test.snail_goal()), Err(Error::Undecided)));
+    }
+
+    #[test]
+    fn move_blanks()
+    {
+        let test = Grid::new(vec!(
+                                1, 2, 3,
+                                7, 0, 4,
+                                6, 0, 5), 3);
+        assert_eq!(test.get_blanks(), 2);
+        assert_eq!(test.move_all_possible().len(), 5);
+        // Blanks never swap with each other.
+        assert!(test.move_blank(0, Move::Down).is_none());
+        assert!(test.move_blank(1, Move::Up).is_none());
+        let next = test.move_blank(1, Move::Left).unwrap();
+        assert_eq!(next, Grid::new(vec!(1, 2, 3, 7, 0, 4, 0, 6, 5), 3));
+        assert_eq!(test.get_move_to(&next), Some((1, Move::Left)));
+        assert!(test.move_blank(2, Move::Left).is_none());
+    }
+
+    #[test]
+    fn solvable_with_blanks()
+    {
+        let test = Grid::new(vec!(
+                                2, 1, 3,
+                                7, 0, 4,
+                                6, 0, 5), 3);
+        let goal = test.snail_goal();
+        assert_eq!(goal, Grid::new(vec!(1, 2, 3, 0, 0, 4, 7, 6, 5), 3));
+        assert_eq!(test.solvable(), Some(true));
+        assert_eq!(test.reachable(&goal), Some(true));
+    }
+
+    #[test]
+    fn move_zero_torus()
+    {
+        let test = Grid::new(vec!(
+                                0, 2, 3,
+                                1, 8, 4,
+                                7, 6, 5), 3).with_topology(Topology::Torus);
+        let next = test.move_zero(Move::Left).unwrap();
+        assert_eq!(next, Grid::new(vec!(3, 2, 0, 1, 8, 4, 7, 6, 5), 3));
+        assert_eq!(test.get_move_to(&next), Some((0, Move::Left)));
+        let next = test.move_zero(Move::Up).unwrap();
+        assert_eq!(next, Grid::new(vec!(7, 2, 3, 1, 8, 4, 0, 6, 5), 3));
+        assert_eq!(test.get_move_to(&next), Some((0, Move::Up)));
+        assert_eq!(test.move_all_possible().len(), 4);
+    }
+
+    #[test]
+    fn manhattan_torus()
+    {
+        let goal = Grid::new(vec!(1, 2, 3, 8, 0, 4, 7, 6, 5), 3).with_topology(Topology::Torus);
+        let test = Grid::new(vec!(1, 2, 5, 3, 0, 6, 7, 4, 8), 3).with_topology(Topology::Torus);
+        assert_eq!(test.manhattan(&goal), 9);
+        assert_eq!(test.linear_conflict(&goal), 0);
+    }
+
+    #[test]
+    fn solvable_torus()
+    {
+        // Unsolvable on a flat table but any 3x3 state is solvable on a torus.
+        let test = Grid::new(vec!(2, 1, 3, 8, 0, 4, 7, 6, 5), 3).with_topology(Topology::Torus);
+        assert_eq!(test.solvable(), Some(true));
+        let test = Grid::new(vec!(1, 2, 3, 4, 12, 13, 14, 5, 11, 0, 15, 6, 10, 9, 8, 7), 4).with_topology(Topology::Torus);
+        assert_eq!(test.solvable(), Some(true));
+        let test = Grid::new(vec!(1, 2, 3, 4, 12, 13, 14, 5, 11, 15, 0, 6, 10, 9, 8, 7), 4).with_topology(Topology::Torus);
+        assert_eq!(test.solvable(), Some(true));
+        let test = Grid::new(vec!(2, 1, 3, 4, 12, 13, 14, 5, 11, 0, 15, 6, 10, 9, 8, 7), 4).with_topology(Topology::Torus);
+        assert_eq!(test.solvable(), Some(false));
+    }
+
+    #[test]
+    fn solvable_to()
+    {
+        let ordered = Grid::new(vec!(1, 2, 3, 4, 5, 6, 7, 8, 0), 3);
+        let test = Grid::new(vec!(1, 2, 3, 4, 5, 6, 0, 7, 8), 3);
+        assert_eq!(test.solvable_to(&ordered), Some(true));
+        assert_eq!(test.solvable_to(&test.snail_goal()), test.solvable());
+        let test = Grid::new(vec!(2, 1, 3, 4, 5, 6, 7, 8, 0), 3);
+        assert_eq!(test.solvable_to(&ordered), Some(false));
+        // The classic unsolvable 15-puzzle with 14 and 15 swapped.
+        let ordered = Grid::new((1..16).chain(0..1).collect(), 4);
+        let test = Grid::new(vec!(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 15, 14, 0), 4);
+        assert_eq!(test.solvable_to(&ordered), Some(false));
+        assert_eq!(ordered.move_zero(Move::Up).unwrap().solvable_to(&ordered), Some(true));
+    }
+
+    #[test]
+    fn rank()
+    {
+        let (grid, goal) = four_by_four();
+        for grid in [&grid, &goal]
+        {
+            assert_eq!(Grid::from_rank(grid.rank().unwrap(), 4), *grid);
+        }
+        // Every grid up to two moves away gets a rank of its own.
+        let around: HashSet<Grid> = grid.move_all_possible().iter().flat_map(Grid::move_all_possible).chain(grid.move_all_possible()).collect();
+        let ranks: HashSet<u64> = around.iter().filter_map(Grid::rank).collect();
+        assert_eq!(ranks.len(), around.len());
+        assert_eq!(Grid::new((0..9).collect(), 3).rank(), Some(0));
+        assert_eq!(Grid::new((0..16).rev().collect(), 4).rank(), Some(permutation::count(16, 16) - 1));
+        assert_eq!(Grid::new(vec!(1, 2, 3, 8, 0, WALL, 7, 6, 5), 3).rank(), None);
+        assert_eq!(Grid::new(vec!(1, 2, 3, 0, 0, 4, 7, 6, 5), 3).rank(), None);
+        assert_eq!(Grid::new((0..25).collect(), 5).rank(), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip()
+    {
+        let test = Grid::new(vec!(1, 2, 3, WALL, 0, 4, 5, 6, 0), 3).with_topology(Topology::Torus);
+        let json = serde_json::to_string(&test).unwrap();
+        assert_eq!(json, format!("{{\"map\":[1,2,3,{},0,4,5,6,0],\"lines\":3,\"topology\":\"Torus\"}}", WALL));
+        let back: Grid = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, test);
+        assert_eq!(back.get_blanks(), 2);
+        assert_eq!(back.get_topology(), Topology::Torus);
+        let moves: Vec<Move> = serde_json::from_str(&serde_json::to_string(&[Move::Up, Move::Left]).unwrap()).unwrap();
+        assert_eq!(moves, [Move::Up, Move::Left]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_rejects_invalid()
+    {
+        let error = serde_json::from_str::<Grid>("{\"map\":[1,2],\"lines\":3}").unwrap_err();
+        assert!(error.to_string().contains("must hold 9 tiles, found 2"));
+        let error = serde_json::from_str::<Grid>("{\"map\":[1,2,3,8,0,4,7,6,3],\"lines\":3}").unwrap_err();
+        assert!(error.to_string().contains(&ParseError::DuplicateTile(3).to_string()));
+        assert!(serde_json::from_str::<Grid>("{\"map\":[1,2,3,8,1,4,7,6,5],\"lines\":3}").is_err());
+        assert!(serde_json::from_str::<Grid>("{\"map\":[],\"lines\":0}").is_err());
     }
 
     #[test]
     fn new_random()
     {
-        let puzzle = Grid::new_random(4);
+        let puzzle = Grid::new_random(4, &mut rand::thread_rng());
         println!("{}", puzzle);
     }
+
+    #[test]
+    fn to_puzzle_file()
+    {
+        let test = Grid::new(vec!(1, 2, 3, 4, 12, 13, 14, 5, 11, 0, 15, WALL, 10, 9, 8, 7), 4);
+        assert_eq!(test.to_puzzle_file(&[String::from("comment")]), "# comment\n4\n 1  2  3  4\n12 13 14  5\n11  0 15  X\n10  9  8  7\n");
+    }
+
+    #[test]
+    fn new_random_uniform()
+    {
+        for lines in 3..9
+        {
+            let puzzle = Grid::new_random_uniform(lines, &mut seeded_rng(lines as u64));
+            assert_eq!(puzzle.solvable(), Some(true));
+            assert_eq!(puzzle.swap_tiles().solvable(), Some(false));
+        }
+    }
+
+    #[test]
+    fn new_random_seeded()
+    {
+        let puzzle = Grid::new_random(4, &mut seeded_rng(42));
+        assert_eq!(puzzle, Grid::new_random(4, &mut seeded_rng(42)));
+        assert_eq!(puzzle.solvable(), Some(true));
+    }
 }
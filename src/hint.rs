@@ -0,0 +1,129 @@
+use crate::{
+    grid::{Grid, Move},
+    algo::{Algo, Settings},
+    error::Error,
+};
+
+// Where a position stands: its next optimal move (none once on the goal) and the cost left.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Hint
+{
+    pub next: Option<(usize, Move)>,
+    pub distance: u32
+}
+
+// Optimal cost to the goal before and after a proposed move.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MoveCheck
+{
+    pub before: u32,
+    pub after: u32
+}
+
+impl MoveCheck
+{
+    pub fn increases(&self) -> bool
+    {
+        self.after > self.before
+    }
+}
+
+// Hints must be optimal, so the weight is kept at 1 and greedy search is off whatever the settings.
+fn solve(grid: &Grid, goal: &Grid, settings: &Settings) -> Result<Algo, Error>
+{
+    let grid = grid.clone().with_topology(settings.topology);
+    let goal = goal.clone().with_topology(settings.topology);
+    grid.check_solvable_to(&goal)?;
+    let settings = Settings { max_weight: Some(1), greedy: false, jobs: settings.jobs, c_type: settings.c_type.clone(), ..*settings };
+    let g_max = settings.g_max;
+    let mut algo = Algo::with_settings(grid, goal, &settings);
+    if algo.resolve()
+    {
+        Ok(algo)
+    }
+    else if algo.limit_reached()
+    {
+        Err(Error::LimitReached(g_max))
+    }
+    else {
+        Err(Error::NoSolution)
+    }
+}
+
+pub fn hint(grid: &Grid, goal: &Grid, settings: &Settings) -> Result<Hint, Error>
+{
+    let algo = solve(grid, goal, settings)?;
+    Ok(Hint { next: algo.get_moves().first().copied(), distance: algo.get_total_cost() })
+}
+
+// The blank a move given without one is made by: the one the hint moves, as it is the only
+// one a player is told about when there are several.
+pub fn hinted_blank(grid: &Grid, goal: &Grid, settings: &Settings) -> Result<usize, Error>
+{
+    if grid.get_blanks() < 2
+    {
+        return Ok(0);
+    }
+    Ok(hint(grid, goal, settings)?.next.map_or(0, |(blank, _)| blank))
+}
+
+// Fails with `Error::Usage` when the blank can't make this move.
+pub fn check_move(grid: &Grid, goal: &Grid, settings: &Settings, (blank, mov): (usize, Move)) -> Result<MoveCheck, Error>
+{
+    let next = grid.move_blank(blank, mov).ok_or_else(|| Error::Usage(format!("The blank can't move {}", mov)))?;
+    let before = solve(grid, goal, settings)?.get_total_cost();
+    let after = solve(&next, goal, settings)?.get_total_cost();
+    Ok(MoveCheck { before, after })
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::{algo::AType, fixtures::{four_by_four, FOUR_BY_FOUR_COST}};
+
+    #[test]
+    fn hints()
+    {
+        let goal = Grid::new(vec!(1, 2, 3, 8, 0, 4, 7, 6, 5), 3);
+        let grid = Grid::new(vec!(1, 2, 3, 0, 8, 4, 7, 6, 5), 3);
+        for a_type in [AType::AStar, AType::IDAStar]
+        {
+            let settings = Settings { a_type, ..Settings::default() };
+            assert_eq!(hint(&grid, &goal, &settings).unwrap(), Hint { next: Some((0, Move::Right)), distance: 1 });
+            assert_eq!(hint(&goal, &goal, &settings).unwrap(), Hint { next: None, distance: 0 });
+            let check = check_move(&grid, &goal, &settings, (0, Move::Up)).unwrap();
+            assert_eq!(check, MoveCheck { before: 1, after: 2 });
+            assert!(check.increases());
+            assert!(!check_move(&grid, &goal, &settings, (0, Move::Right)).unwrap().increases());
+            assert!(matches!(check_move(&grid, &goal, &settings, (0, Move::Left)), Err(Error::Usage(_))));
+        }
+        // With two blanks the move is checked on the blank the hint names.
+        let blanks_goal = Grid::new(vec!(1, 2, 3, 0, 0, 4, 7, 6, 5), 3);
+        let blanks = Grid::new(vec!(1, 2, 3, 0, 4, 0, 7, 6, 5), 3);
+        let settings = Settings::default();
+        assert_eq!(hint(&blanks, &blanks_goal, &settings).unwrap().next, Some((1, Move::Left)));
+        let blank = hinted_blank(&blanks, &blanks_goal, &settings).unwrap();
+        assert_eq!(blank, 1);
+        assert_eq!(check_move(&blanks, &blanks_goal, &settings, (blank, Move::Left)).unwrap(), MoveCheck { before: 1, after: 0 });
+        let unsolvable = Grid::new(vec!(2, 1, 3, 8, 0, 4, 7, 6, 5), 3);
+        assert!(matches!(hint(&unsolvable, &goal, &Settings::default()), Err(Error::Unsolvable)));
+    }
+
+    #[test]
+    fn hint_is_optimal()
+    {
+        // The weight and greedy search asked for are ignored, so following the hints takes
+        // exactly the optimal number of moves.
+        let (mut grid, goal) = four_by_four();
+        let settings = Settings { max_weight: Some(10), greedy: true, ..Settings::default() };
+        for distance in (1..=FOUR_BY_FOUR_COST).rev()
+        {
+            let next = hint(&grid, &goal, &settings).unwrap();
+            assert_eq!(next.distance, distance);
+            let (blank, mov) = next.next.unwrap();
+            grid = grid.move_blank(blank, mov).unwrap();
+        }
+        assert_eq!(grid, goal);
+    }
+}
@@ -0,0 +1,16 @@
+pub mod state;
+pub mod grid;
+pub mod node;
+pub mod puzzle_gen;
+pub mod algo;
+pub mod cost;
+pub mod parser;
+pub mod batch;
+pub mod error;
+pub mod hint;
+pub mod render;
+pub mod display;
+pub mod table;
+pub mod weight;
+#[cfg(test)]
+mod fixtures;
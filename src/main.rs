@@ -3,81 +3,27 @@
 extern crate clap;
 extern crate utils;
 
-mod state;
-mod grid;
-mod node;
-mod puzzle_gen;
-mod algo;
-use clap::{Arg, App, AppSettings};
-use std::{path::Path, fs};
-use grid::{Grid, HType};
-use node::Node;
-use state::State;
-use algo::{Algo, AType};
+mod tui;
+mod play;
+mod animate;
 
-fn sort_check_and_dedup(mut input: Vec<u16>) -> bool
-{
-    let len = input.len();
-    input.sort_unstable();
-    input.dedup();
-    // The line below could seem weird as there is no `if` but in fact this line already returns a `bool` as expected.
-    input.len() == len && *input.last().unwrap() == len as u16 - 1  && *input.first().unwrap() == 0
-}
-
-fn parser(content: String) -> Result<Grid, String>
-{
-    let mut ret: Vec<u16> = Vec::new();
-    let mut content_lines = utils::remove_comment_by_line(&content, "#");
-    let mut nb_lines = content_lines.len();
-
-    let nb_col = if nb_lines > 0
-    {
-        let first = content_lines.remove(0);
-        nb_lines -= 1;
-        match first.parse::<usize>()
-        {
-            Ok(col) => col,
-            Err(_) => return Err(format!("Invalid puzzle size: [{}]", first))
-        }
-    }
-    else {
-        return Err("There is no way we can resolve an empty puzzle dummy!".into());
-    };
-    if nb_col != nb_lines
-    {
-        return Err("The size definition and the number of line of the puzzle don't match".into());
-    }
-    for line in content_lines
-    {
-        let mut invalid_token = false;
-        let mut parsed_line: Vec<u16> = line.split_whitespace().map(|x| {
-            let res = x.parse::<u16>();
-            if res.is_err()
-            {
-                invalid_token = true;
-            }
-            res.unwrap_or(0)
-        }).collect();
-        if invalid_token
-        {
-            return Err(format!("At least one invalid token found in the following line: {}", line));
-        }
-        else if parsed_line.len() != nb_lines
-        {
-            return Err(format!("Invalid puzzle format: we have {} lines but the following line contains {} columns: {}", nb_lines, parsed_line.len(), line));
-        }
-        ret.append(&mut parsed_line);
-    }
-    if sort_check_and_dedup(ret.clone())
-    {
-        // No need to clone `ret` here because it will be dropped at the end
-        // of this function so we can safely give ownership to the new `Grid`.
-        Ok(Grid::new(ret, nb_lines as u8))
-    }
-    else {
-        Err("Invalid puzzle format".into())
-    }
-}
+use clap::{Arg, App, AppSettings, ArgMatches, SubCommand};
+use std::{path::Path, fs, fmt::{self, Write}, io::{self, Read, IsTerminal}, process::ExitCode, time::Duration};
+use rand::{Rng, seq::SliceRandom};
+use n_puzzle::{
+    grid::{Grid, HType, Move, Topology},
+    puzzle_gen::{self, GType},
+    algo::{Algo, AType, Settings},
+    cost::CType,
+    weight::WType,
+    parser::{parse_puzzle, InputFormat},
+    batch::{self, SummaryFormat},
+    error::Error,
+    hint,
+    render::{self, RenderFormat},
+    display::DisplayStyle,
+    table::{self, DistanceTable},
+};
 
 fn expect_size(nbr: String) -> Result<(), String>
 {
@@ -121,6 +67,42 @@ fn expect_gscore(nbr: String) -> Result<(), String>
     Err(String::from("Expected a number"))
 }
 
+fn expect_jobs(nbr: String) -> Result<(), String>
+{
+    match nbr.parse::<usize>()
+    {
+        Ok(n) if n > 0 => Ok(()),
+        _ => Err(String::from("Expected a number greater than 0"))
+    }
+}
+
+fn expect_iterations(nbr: String) -> Result<(), String>
+{
+    match nbr.parse::<u32>()
+    {
+        Ok(_) => Ok(()),
+        Err(_) => Err(String::from("Expected a number between 0 and U32MAX"))
+    }
+}
+
+fn expect_millis(nbr: String) -> Result<(), String>
+{
+    match nbr.parse::<u64>()
+    {
+        Ok(_) => Ok(()),
+        Err(_) => Err(String::from("Expected a number of milliseconds between 0 and U64MAX"))
+    }
+}
+
+fn expect_seed(nbr: String) -> Result<(), String>
+{
+    match nbr.parse::<u64>()
+    {
+        Ok(_) => Ok(()),
+        Err(_) => Err(String::from("Expected a number between 0 and U64MAX"))
+    }
+}
+
 fn expect_file(file: String) -> Result<(), String>
 {
     if Path::new(&file).exists()
@@ -135,66 +117,469 @@ fn expect_file(file: String) -> Result<(), String>
     Err(String::from("Path is invalid/does not exist."))
 }
 
-fn error_handler<T, E>(from: Result<T, E>) -> T
-where E:
-    std::fmt::Display
+fn expect_input(file: String) -> Result<(), String>
+{
+    if file == "-"
+    {
+        Ok(())
+    }
+    else {
+        expect_file(file)
+    }
+}
+
+// `-` or no input file at all (when something is piped in) means the puzzle comes from stdin.
+fn read_input(input: Option<&str>) -> io::Result<String>
+{
+    match input
+    {
+        Some("-") | None => {
+            let mut content = String::new();
+            io::stdin().read_to_string(&mut content)?;
+            Ok(content)
+        },
+        Some(file) => fs::read_to_string(Path::new(file))
+    }
+}
+
+fn usage<T>(from: Result<T, String>) -> Result<T, Error>
+{
+    from.map_err(Error::Usage)
+}
+
+fn generate(matches: &ArgMatches) -> Result<(), Error>
+{
+    let lines: u8 = matches.value_of("size").unwrap().parse().unwrap();
+    let seed: u64 = match matches.value_of("seed")
+    {
+        Some(x) => x.parse().unwrap(),
+        None => rand::random()
+    };
+    let mut rng = puzzle_gen::seeded_rng(seed);
+    let (grid, solvable, comment) = if let Some(distance) = matches.value_of("distance")
+    {
+        let distance: u32 = distance.parse().unwrap();
+        (puzzle_gen::create_at_distance(lines, distance, &mut rng).map_err(Error::Generation)?, true, format!("Optimal solution: {} moves", distance))
+    }
+    else if matches.is_present("hardest")
+    {
+        let (grids, distance) = puzzle_gen::create_hardest(lines).map_err(Error::Generation)?;
+        (grids.choose(&mut rng).cloned().ok_or_else(|| Error::Generation(String::from("No puzzle found")))?, true, format!("Optimal solution: {} moves", distance))
+    }
+    else {
+        let solvable = if matches.is_present("solvable") || matches.is_present("unsolvable")
+        {
+            matches.is_present("solvable")
+        }
+        else {
+            rng.gen()
+        };
+        let iterations = match matches.value_of("iterations")
+        {
+            Some(x) => x.parse().unwrap(),
+            None => puzzle_gen::DEFAULT_ITERATIONS
+        };
+        (puzzle_gen::make_puzzle(lines, solvable, iterations, &mut rng), solvable, format!("Random walk of {} moves from the goal", iterations))
+    };
+    let solvable = String::from(if solvable { "This puzzle is solvable" } else { "This puzzle is unsolvable" });
+    let content = grid.to_puzzle_file(&[solvable, comment, format!("Seed: {}", seed)]);
+    match matches.value_of("output")
+    {
+        Some(file) => fs::write(file, content).map_err(|e| Error::io(file, e))?,
+        None => print!("{}", content)
+    }
+    Ok(())
+}
+
+// Shared by the solver and the subcommands running it.
+fn heuristic_arg<'a, 'b>() -> Arg<'a, 'b>
+{
+    Arg::with_name("heuristic")
+        .short("e")
+        .long("heuristic")
+        .number_of_values(1)
+        .possible_values(&["hamming", 
+                        "manhattan", 
+                        "linear_manhattan"])
+        .help("Choose heuristic model. Default is linear manhattan (fastest).")
+}
+
+fn algorithm_arg<'a, 'b>() -> Arg<'a, 'b>
+{
+    Arg::with_name("algorithm")
+        .short("a")
+        .long("algorithm")
+        .number_of_values(1)
+        .possible_values(&["astar", "idastar", "bfs", "arastar"])
+        .help("Choose algorithm, bfs ignores the heuristic and finds the fewest moves, arastar gives a first solution quickly then improves it. Default is idastar.")
+}
+
+// Reads a puzzle file (`-` or `None` for stdin) and its goal, the snail unless the file gives one.
+fn read_puzzle(input: Option<&str>) -> Result<(Grid, Grid), Error>
+{
+    let name = input.unwrap_or("-");
+    let (grid, goal) = parse_puzzle(read_input(input).map_err(|e| Error::io(name, e))?, InputFormat::Auto)?;
+    let goal = goal.unwrap_or_else(|| grid.snail_goal());
+    Ok((grid, goal))
+}
+
+fn hint(matches: &ArgMatches) -> Result<(), Error>
+{
+    let (grid, goal) = read_puzzle(matches.value_of("input"))?;
+    let settings = Settings
+    {
+        h_type: usage(HType::from_str_or_default(matches.value_of("heuristic")))?,
+        a_type: usage(AType::from_str_or_default(matches.value_of("algorithm")))?,
+        ..Settings::default()
+    };
+    // Puzzles missing from the table are searched as usual.
+    let table = matches.value_of("table").map(DistanceTable::load).transpose()?;
+    match matches.value_of("move")
+    {
+        Some(mov) => {
+            let mov = match mov
+            {
+                "up" => Move::Up,
+                "down" => Move::Down,
+                "right" => Move::Right,
+                _ => Move::Left
+            };
+            let blank = hint::hinted_blank(&grid, &goal, &settings)?;
+            let check = match table.and_then(|t| t.check_move(&grid, &goal, (blank, mov)))
+            {
+                Some(check) => check,
+                None => hint::check_move(&grid, &goal, &settings, (blank, mov))?
+            };
+            let verdict = match check.after.cmp(&check.before)
+            {
+                std::cmp::Ordering::Greater => "increases",
+                std::cmp::Ordering::Less => "decreases",
+                std::cmp::Ordering::Equal => "keeps"
+            };
+            println!("Moving {} {} the distance to the goal: {} -> {}", mov, verdict, check.before, check.after);
+        },
+        None => {
+            let hint = match table.and_then(|t| t.hint(&grid, &goal))
+            {
+                Some(hint) => hint,
+                None => hint::hint(&grid, &goal, &settings)?
+            };
+            match hint.next
+            {
+                Some((_, mov)) if grid.get_blanks() == 1 => println!("Next move:\t\t\t{}", mov),
+                Some((blank, mov)) => {
+                    let cell = grid.get_map().iter().enumerate().filter(|(_, &t)| t == 0).nth(blank).map_or(0, |(i, _)| i);
+                    let lines = grid.get_lines() as usize;
+                    println!("Next move:\t\t\t{} (blank on line {}, column {})", mov, cell / lines + 1, cell % lines + 1)
+                },
+                None => println!("The puzzle is already solved")
+            }
+            println!("Remaining moves:\t\t{}", hint.distance);
+        }
+    }
+    Ok(())
+}
+
+fn table(matches: &ArgMatches) -> Result<(), Error>
+{
+    let output = matches.value_of("output").unwrap();
+    let table = DistanceTable::build();
+    table.save(output)?;
+    println!("Distances of the {} solvable 3x3 puzzles written to {}, the farthest is {} moves away", table::TABLE_SIZE, output, table.max_distance());
+    Ok(())
+}
+
+fn play(matches: &ArgMatches) -> Result<(), Error>
+{
+    let (grid, goal) = match matches.value_of("input")
+    {
+        Some(input) => read_puzzle(Some(input))?,
+        None => {
+            let lines: u8 = matches.value_of("size").map_or(3, |x| x.parse().unwrap());
+            let seed: u64 = matches.value_of("seed").map_or_else(rand::random, |x| x.parse().unwrap());
+            let grid = GType::default().generate(lines, &mut puzzle_gen::seeded_rng(seed));
+            let goal = grid.snail_goal();
+            (grid, goal)
+        }
+    };
+    grid.check_solvable_to(&goal)?;
+    play::play(grid, goal)
+}
+
+// Text of a run, `err` goes to stderr and `error` is set when the puzzle could not be solved.
+struct Report
 {
-    match from
+    out: String,
+    err: String,
+    error: Option<Error>,
+    // Every grid from the initial state to the goal when solved.
+    steps: Vec<Grid>
+}
+
+// How a report shows the grids, the steps are left out when they are going to be animated.
+#[derive(Copy, Clone)]
+struct ReportStyle
+{
+    animate: bool,
+    display: DisplayStyle,
+    // Print the solutions ARA* improves on as they come, only when a single puzzle is solved.
+    stream: bool
+}
+
+// Solves one puzzle and renders its report instead of printing it, so puzzles solved
+// concurrently can still be displayed in the order they were given.
+// `goal` defaults to the snail.
+fn solve_report(grid: Grid, goal: Option<Grid>, settings: &Settings, random: Option<(u64, GType)>, style: ReportStyle) -> Result<Report, fmt::Error>
+{
+    let mut out = String::new();
+    let mut err = String::new();
+    let grid = grid.with_topology(settings.topology);
+    let goal = goal.map_or_else(|| grid.snail_goal(), |g| g.with_topology(settings.topology));
+    let show = |g: &Grid| style.display.render(g, &goal);
+    writeln!(out, "{}", show(&grid))?;
+    if let (None, Err(e)) = (random, grid.check_solvable_to(&goal))
+    {
+        return Ok(Report { out, err, error: Some(e), steps: Vec::new() });
+    }
+    if let Err(e) = settings.c_type.check_tiles(&grid)
+    {
+        return Ok(Report { out, err, error: Some(Error::Usage(e)), steps: Vec::new() });
+    }
+    let Settings { h_type, a_type, ref c_type, w_type, topology, g_max, greedy, .. } = *settings;
+    let max_weight = settings.get_max_weight(grid.get_lines());
+    let mut algo = Algo::with_settings(grid.clone(), goal.clone(), settings);
+    let solved = algo.resolve_with(|i| if style.stream
+    {
+        eprintln!("Improved solution: cost {} after {} ms with weight {}, at most {:.3} times the optimal cost", i.cost, i.elapsed.as_millis(), i.weight, i.bound);
+    });
+    let w = if solved { &mut out } else { &mut err };
+    if solved
+    {
+        writeln!(w, "A solution was found for the initial state you gave\nHere are the results:\n")?;
+        if a_type == AType::AraStar && style.stream
+        {
+            writeln!(w, "Solutions found along the way:\t{}\n", algo.get_improvements().len())?;
+        }
+        else if a_type == AType::AraStar
+        {
+            writeln!(w, "Solutions found along the way:\n")?;
+            for i in algo.get_improvements()
+            {
+                writeln!(w, "Cost {} after {} ms with weight {}, at most {:.3} times the optimal cost", i.cost, i.elapsed.as_millis(), i.weight, i.bound)?;
+            }
+            writeln!(w)?;
+        }
+        if !style.animate
+        {
+            writeln!(w, "Steps to reach the goal:\n")?;
+            for step in algo.get_steps()
+            {
+                writeln!(w, "{}", show(&step))?;
+                writeln!(w, "===================================\n")?;
+            }
+        }
+        let moves: Vec<String> = algo.get_moves().iter().map(|(blank, m)| {
+            if grid.get_blanks() > 1
+            {
+                format!("{}:{}", blank + 1, m)
+            }
+            else {
+                m.to_string()
+            }
+        }).collect();
+        writeln!(w, "Sequence of moves:\t\t{}\n", moves.join(" "))?;
+        writeln!(w, "Amount of moves required:\t{}\n", moves.len())?;
+        if *c_type != CType::Unit
+        {
+            writeln!(w, "Total cost of the moves:\t{}\n", algo.get_total_cost())?;
+        }
+        writeln!(w, "Complexity in time:\t\t{}\n(number of nodes processed)\n", algo.get_t_complex())?;
+        writeln!(w, "Complexity in size:\t\t{}\n(number of nodes in memory at the same time)", algo.get_s_complex())?;
+        writeln!(w, "Higher weight reached:\t\t{}\n", algo.get_weight())?;
+        match algo.get_bound()
+        {
+            Some(bound) => writeln!(w, "Suboptimality bound:\t\t{}\n(the cost is at most this many times the optimal cost)\n\n", bound)?,
+            None => writeln!(w, "Suboptimality bound:\t\tnone\n\n")?
+        }
+    }
+    else {
+        writeln!(w, "There is no way the provided n-puzzle can reach the goal:\nInitial state:\n{}Goal state:\n{}\n", show(&grid), show(&goal))?;
+    }
+    writeln!(w, "As reminder here are the settings you requested:\n")?;
+    writeln!(w, "Algorithm:\t\t\t{}", a_type)?;
+    writeln!(w, "Heuristic:\t\t\t{}", h_type)?;
+    writeln!(w, "Topology:\t\t\t{}", topology)?;
+    if let Some((seed, g_type)) = random
+    {
+        writeln!(w, "Random grid seed:\t\t{}", seed)?;
+        writeln!(w, "Random grid generation:\t\t{}", g_type)?;
+    }
+    writeln!(w, "Move cost:\t\t\t{}", c_type)?;
+    writeln!(w, "Maximum weight:\t\t\t{}", max_weight)?;
+    if matches!(a_type, AType::AStar | AType::IDAStar)
+    {
+        writeln!(w, "Weighting:\t\t\t{}", w_type)?;
+    }
+    writeln!(w, "Greedy search:\t\t\t{}", greedy)?;
+    writeln!(w, "Uniform cost search max cost:\t{}", g_max)?;
+    let error = if solved
+    {
+        None
+    }
+    else if algo.limit_reached()
+    {
+        Some(Error::LimitReached(g_max))
+    }
+    else {
+        Some(Error::NoSolution)
+    };
+    let steps = if solved { algo.get_steps() } else { Vec::new() };
+    Ok(Report { out, err, error, steps })
+}
+
+fn main() -> ExitCode
+{
+    match run()
     {
-        Ok(res) => res,
+        Ok(()) => ExitCode::SUCCESS,
         Err(e) => {
             eprintln!("ERROR: {}", e);
-            std::process::exit(42);
+            ExitCode::from(e.exit_code() as u8)
         }
     }
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> 
+fn run() -> Result<(), Error>
 {
-    let matches = App::new("N-Puzzle")
+    let mut app = App::new("N-Puzzle")
                 .version(crate_version!())
                 .author(crate_authors!())
                 .about(crate_description!())
-                .setting(AppSettings::ArgRequiredElseHelp)
+                .after_help("EXIT CODES:\n    0    Success\n    1    Invalid command line or option value\n    2    A file could not be read or written\n    3    The puzzle is malformed\n    4    The puzzle is unsolvable\n    5    The uniform cost limit (-u) was reached before the goal\n    6    The search ended without reaching the goal\n    7    No puzzle could be generated with the requested constraints\n    8    The puzzle has walls and too many states to tell whether it is solvable\nWith several input files, the code of the first puzzle that failed is used.")
+                .setting(AppSettings::SubcommandsNegateReqs)
+                .setting(AppSettings::ArgsNegateSubcommands)
                 .arg(Arg::with_name("input")
                     .index(1)
+                    .multiple(true)
+                    .conflicts_with_all(&["random", "batch"])
+                    .validator(expect_input)
+                    .help("Input files, `-` or nothing with a piped input reads stdin. Must be square, solvable, larger than 2 by 2 and smaller than 9 by 9. Cells marked X are walls."))
+                .arg(Arg::with_name("input_format")
+                    .long("input-format")
                     .number_of_values(1)
-                    .conflicts_with("random")
-                    .required_unless("random")
-                    .validator(expect_file)
-                    .help("Input file. Must be square, solvable, larger than 2 by 2 and smaller than 9 by 9."))
+                    .possible_values(&["auto", "42", "line", "csv", "json"])
+                    .help("Format of the input files. `42`: size line then rows, `line`: `3: 1 2 3 8 0 4 7 6 5`, `csv`: rows of comma separated tiles, `json`: {\"size\": 3, \"tiles\": [...], \"goal\": [...]} with an optional goal. Default is auto, guessed from the first line."))
                 .arg(Arg::with_name("random")
                     .short("r")
                     .long("random")
                     .number_of_values(1)
                     .validator(expect_size)
                     .help("Generate random grid (between 3 and 8)."))
-                .arg(Arg::with_name("heuristic")
-                    .short("e")
-                    .long("heuristic")
+                .arg(Arg::with_name("batch")
+                    .short("b")
+                    .long("batch")
+                    .number_of_values(1)
+                    .conflicts_with("random")
+                    .help("Solve every puzzle of a directory, of the files matching a glob (e.g. `puzzles/*.txt`) or of a file holding several puzzles, then print a summary table."))
+                .arg(Arg::with_name("jobs")
+                    .short("j")
+                    .long("jobs")
+                    .number_of_values(1)
+                    .validator(expect_jobs)
+                    .help("Number of threads. Several input files or a batch are solved this many at a time, a single puzzle is explored by this many threads with IDA*. Default is 1."))
+                .arg(Arg::with_name("summary_format")
+                    .long("summary-format")
+                    .number_of_values(1)
+                    .requires("batch")
+                    .possible_values(&["csv", "json"])
+                    .help("Format of the batch summary. Default is csv."))
+                .arg(Arg::with_name("summary")
+                    .long("summary")
+                    .number_of_values(1)
+                    .requires("batch")
+                    .help("Write the batch summary to this file instead of the standard output."))
+                .arg(Arg::with_name("seed")
+                    .short("s")
+                    .long("seed")
+                    .number_of_values(1)
+                    .requires("random")
+                    .validator(expect_seed)
+                    .help("Seed used to generate the random grid, a new one is picked and printed if not set."))
+                .arg(Arg::with_name("generation")
+                    .long("generation")
+                    .number_of_values(1)
+                    .requires("random")
+                    .possible_values(&["walk", "uniform"])
+                    .help("Choose how the random grid is generated, uniform picks any solvable state with the same probability. Default is walk."))
+                .arg(heuristic_arg())
+                .arg(Arg::with_name("topology")
+                    .short("t")
+                    .long("topology")
+                    .number_of_values(1)
+                    .possible_values(&["flat", "torus"])
+                    .help("Choose board topology, moves wrap across the edges on a torus. Default is flat."))
+                .arg(algorithm_arg())
+                .arg(Arg::with_name("cost")
+                    .short("c")
+                    .long("cost")
                     .number_of_values(1)
-                    .possible_values(&["hamming", 
-                                    "manhattan", 
-                                    "linear_manhattan"])
-                    .help("Choose heuristic model. Default is linear manhattan (fastest)."))
-                .arg(Arg::with_name("algorithm")
-                    .short("a")
-                    .long("algorithm")
+                    .possible_values(&["unit", "tile_value"])
+                    .help("Choose the cost of moving a tile, tile_value means moving tile k costs k. Default is unit."))
+                .arg(Arg::with_name("cost_table")
+                    .long("cost-table")
                     .number_of_values(1)
-                    .possible_values(&["astar", "idastar"])
-                    .help("Choose algorithm. Default is idastar."))
+                    .conflicts_with("cost")
+                    .validator(expect_file)
+                    .help("Read the cost of moving each tile from a file made of `<tile> <cost>` lines. Missing tiles cost 1, costs go up to 1000 and every tile must be on the board."))
                 .arg(Arg::with_name("weight")
                     .short("w")
                     .long("weight")
                     .number_of_values(1)
                     .validator(expect_weight)
                     .help("Force heuristic max weight to value. Max is 100."))
+                .arg(Arg::with_name("weighting")
+                    .long("weighting")
+                    .number_of_values(1)
+                    .possible_values(&["constant", "progressive", "dynamic", "pwxd"])
+                    .help("Choose how astar and idastar weight the heuristic up to the max weight: from the start, raised as the search goes on, lowered with depth (Pohl) or piecewise linear (pwXD). Default is progressive."))
                 .arg(Arg::with_name("uniform")
                     .short("u")
                     .long("uniform")
                     .validator(expect_gscore)
                     .number_of_values(1)
                     .help("Set heuristic model variant to uniform cost with value. Max is u32_max"))
+                .arg(Arg::with_name("time_limit")
+                    .long("time-limit")
+                    .number_of_values(1)
+                    .validator(expect_millis)
+                    .help("Milliseconds arastar spends improving its solution once it has found one. Default is until it is optimal."))
+                .arg(Arg::with_name("display")
+                    .long("display")
+                    .number_of_values(1)
+                    .possible_values(&["tabs", "boxed", "color", "line"])
+                    .help("How grids are printed: tab separated, boxed with aligned columns, boxed with the tiles already in place in green, or on a single line. Default is tabs."))
+                .arg(Arg::with_name("animate")
+                    .long("animate")
+                    .conflicts_with("batch")
+                    .takes_value(false)
+                    .help("Replay the solution in place instead of printing every step: space pauses, left/right step through it, q quits."))
+                .arg(Arg::with_name("delay")
+                    .long("delay")
+                    .number_of_values(1)
+                    .validator(expect_millis)
+                    .help("Milliseconds between two steps of --animate or of an animated SVG. Default is 300."))
+                .arg(Arg::with_name("render")
+                    .long("render")
+                    .number_of_values(1)
+                    .conflicts_with("batch")
+                    .requires("render_to")
+                    .possible_values(&["svg", "ppm", "svg_strip", "svg_animated"])
+                    .help("Render the steps of the solution as images: an SVG or PPM file per step, every step in a single SVG strip or an animated SVG."))
+                .arg(Arg::with_name("render_to")
+                    .long("render-to")
+                    .number_of_values(1)
+                    .requires("render")
+                    .help("Where --render writes: a directory for svg and ppm, a file for svg_strip and svg_animated."))
                 .arg(Arg::with_name("greedy")
                     .short("g")
                     .long("greedy")
@@ -202,140 +587,256 @@ fn main() -> Result<(), Box<dyn std::error::Error>>
                     .conflicts_with("weight")
                     .takes_value(false)
                     .help("Set heuristic model variant to greedy."))
-                .get_matches();
+                .subcommand(SubCommand::with_name("generate")
+                    .about("Generate a puzzle file instead of solving one.")
+                    .arg(Arg::with_name("size")
+                        .index(1)
+                        .required(true)
+                        .validator(expect_size)
+                        .help("Size of the puzzle (between 3 and 8)."))
+                    .arg(Arg::with_name("distance")
+                        .short("d")
+                        .long("distance")
+                        .number_of_values(1)
+                        .validator(expect_gscore)
+                        .help("Generate a puzzle whose optimal solution is exactly this many moves (3x3 and 4x4 only). A 4x4 puzzle is searched for 20 seconds at most, which is rarely enough past 40 moves."))
+                    .arg(Arg::with_name("hardest")
+                        .long("hardest")
+                        .conflicts_with("distance")
+                        .takes_value(false)
+                        .help("Generate one of the puzzles farthest from the goal (3x3 only)."))
+                    .arg(Arg::with_name("solvable")
+                        .short("s")
+                        .long("solvable")
+                        .takes_value(false)
+                        .help("Forces generation of a solvable puzzle. Overrides -u."))
+                    .arg(Arg::with_name("unsolvable")
+                        .short("u")
+                        .long("unsolvable")
+                        .conflicts_with_all(&["distance", "hardest"])
+                        .takes_value(false)
+                        .help("Forces generation of an unsolvable puzzle. Either kind is picked at random without -s nor -u."))
+                    .arg(Arg::with_name("iterations")
+                        .short("i")
+                        .long("iterations")
+                        .number_of_values(1)
+                        .conflicts_with_all(&["distance", "hardest"])
+                        .validator(expect_iterations)
+                        .help("Number of random moves of the blank from the goal. Default is 10000."))
+                    .arg(Arg::with_name("seed")
+                        .long("seed")
+                        .number_of_values(1)
+                        .validator(expect_seed)
+                        .help("Seed used to generate the puzzle, a new one is picked if not set."))
+                    .arg(Arg::with_name("output")
+                        .short("o")
+                        .long("output")
+                        .number_of_values(1)
+                        .help("Write the puzzle to this file instead of the standard output.")))
+                .subcommand(SubCommand::with_name("play")
+                    .about("Solve a puzzle yourself in the terminal.")
+                    .arg(Arg::with_name("input")
+                        .index(1)
+                        .validator(expect_input)
+                        .help("Puzzle to play, `-` reads stdin. A random puzzle is played if not set."))
+                    .arg(Arg::with_name("size")
+                        .short("r")
+                        .long("random")
+                        .number_of_values(1)
+                        .conflicts_with("input")
+                        .validator(expect_size)
+                        .help("Size of the random puzzle (between 3 and 8). Default is 3."))
+                    .arg(Arg::with_name("seed")
+                        .long("seed")
+                        .number_of_values(1)
+                        .conflicts_with("input")
+                        .validator(expect_seed)
+                        .help("Seed used to generate the random puzzle, a new one is picked if not set.")))
+                .subcommand(SubCommand::with_name("hint")
+                    .about("Give the next optimal move of a puzzle, or tell whether a move gets closer to the goal.")
+                    .arg(Arg::with_name("input")
+                        .index(1)
+                        .validator(expect_input)
+                        .help("Puzzle to look at, `-` or nothing reads stdin."))
+                    .arg(Arg::with_name("move")
+                        .short("m")
+                        .long("move")
+                        .number_of_values(1)
+                        .possible_values(&["up", "down", "right", "left"])
+                        .help("Check this move of the blank instead of giving the next one. With several blanks, the blank the hint would move makes it."))
+                    .arg(heuristic_arg())
+                    .arg(algorithm_arg())
+                    .arg(Arg::with_name("table")
+                        .long("table")
+                        .number_of_values(1)
+                        .validator(expect_file)
+                        .help("Answer 3x3 puzzles with the snail goal from a distance table built by the table subcommand.")))
+                .subcommand(SubCommand::with_name("table")
+                    .about("Build the optimal distance of every solvable 3x3 puzzle to the snail goal and write it to a file.")
+                    .arg(Arg::with_name("output")
+                        .index(1)
+                        .required(true)
+                        .help("File to write the table to.")));
+    let matches = app.clone().get_matches();
 
-    let grid = if matches.value_of("input").is_some()
+    if let Some(matches) = matches.subcommand_matches("generate")
     {
-        let content = error_handler(fs::read_to_string(Path::new(matches.value_of("input").expect("Invalid input"))));
-        error_handler(parser(content))
+        return generate(matches);
     }
-    else {
-        let lines = matches.value_of("random").unwrap().parse().unwrap();
-        Grid::new_random(lines)
-    };
-    println!("{}", grid);
-    if !matches.is_present("random") && !grid.solvable()
+    if let Some(matches) = matches.subcommand_matches("play")
+    {
+        return play(matches);
+    }
+    if let Some(matches) = matches.subcommand_matches("hint")
+    {
+        return hint(matches);
+    }
+    if let Some(matches) = matches.subcommand_matches("table")
     {
-        error_handler(Err(String::from("Grid is unsolvable !")))
+        return table(matches);
     }
-    let lines = grid.get_lines();
+    if !matches.is_present("input") && !matches.is_present("random") && !matches.is_present("batch") && io::stdin().is_terminal()
+    {
+        app.print_help().map_err(|e| Error::Usage(e.to_string()))?;
+        println!();
+        return Err(Error::Usage(String::from("No puzzle given")));
+    }
+
     let greedy = matches.is_present("greedy");
-    let g_max: u32 = match matches.value_of("uniform")
+    let jobs: usize = matches.value_of("jobs").map_or(1, |x| x.parse().unwrap());
+    let input_format = usage(InputFormat::from_str_or_default(matches.value_of("input_format")))?;
+    let animate = matches.is_present("animate");
+    let mut style = ReportStyle { animate, display: usage(DisplayStyle::from_str_or_default(matches.value_of("display")))?, stream: true };
+    let delay = Duration::from_millis(matches.value_of("delay").map_or(300, |x| x.parse().unwrap()));
+    let render = match matches.value_of("render_to")
     {
-        Some(_) if greedy => u32::MAX,
-        Some(x) => x.parse().unwrap(),
-        None => u32::MAX,
+        Some(path) => Some((usage(RenderFormat::from_str_or_default(matches.value_of("render")))?, path)),
+        None => None
     };
-    let max_weight: u32 = match matches.value_of("weight")
+    let mut settings = Settings
     {
-        Some(_) if greedy => 1,
-        Some(x) => x.parse().unwrap(),
-        None if greedy => 1,
-        None => (u32::from(lines) / 2 + 1),
+        h_type: usage(HType::from_str_or_default(matches.value_of("heuristic")))?,
+        a_type: usage(AType::from_str_or_default(matches.value_of("algorithm")))?,
+        w_type: usage(WType::from_str_or_default(matches.value_of("weighting")))?,
+        c_type: match matches.value_of("cost_table")
+        {
+            Some(file) => usage(CType::from_table(&fs::read_to_string(file).map_err(|e| Error::io(file, e))?))?,
+            None => usage(CType::from_str_or_default(matches.value_of("cost")))?
+        },
+        topology: usage(Topology::from_str_or_default(matches.value_of("topology")))?,
+        max_weight: matches.value_of("weight").map(|x| x.parse().unwrap()),
+        g_max: match matches.value_of("uniform")
+        {
+            Some(_) if greedy => u32::MAX,
+            Some(x) => x.parse().unwrap(),
+            None => u32::MAX,
+        },
+        greedy,
+        jobs: 1,
+        time_limit: matches.value_of("time_limit").map(|x| Duration::from_millis(x.parse().unwrap()))
     };
-    let a_type = error_handler(AType::from_str_or_default(matches.value_of("algorithm")));
-    if greedy && a_type == AType::IDAStar
-    {
-        error_handler(Err(String::from("It's not allowed to perform greedy search with IDA* algorithm\nPlease select another algorithm or remove the use of option `-g`")))
-    }
-    let h_type = error_handler(HType::from_str_or_default(matches.value_of("heuristic")));
-    let mut initial_node = Node::new(State::default(), grid);
-	let goal = Grid::new(puzzle_gen::create_snail_goal(lines), lines as u8);
-    initial_node.update_state(&goal, h_type, 1, greedy);
-    let mut algo = Algo::new(initial_node.clone(), goal.clone(), h_type, a_type, 1, max_weight, g_max, greedy);
-    if algo.resolve()
-    {
-        println!("A solution was found for the initial state you gave\nHere are the results:\n");
-        println!("Steps to reach the goal:\n");
-        algo.print_steps();
-        println!("Amount of moves required:\t{}\n", algo.get_total_cost());
-        println!("Complexity in time:\t\t{}\n(number of nodes processed)\n", algo.get_t_complex());
-        println!("Complexity in size:\t\t{}\n(number of nodes in memory at the same time)", algo.get_s_complex());
-        println!("Higher weight reached:\t\t{}\n\n", algo.get_weight());
-        println!("As reminder here are the settings you requested:\n");
-        println!("Algorithm:\t\t\t{}", a_type);
-        println!("Heuristic:\t\t\t{}", h_type);
-        println!("Maximum weight:\t\t\t{}", max_weight);
-        println!("Greedy search:\t\t\t{}", greedy);
-        println!("Uniform cost search max cost:\t{}", g_max);
-        Ok(())
-    }
-    else {
-        eprintln!("There is no way the provided n-puzzle can reach the goal:\nInitial state:\n{}Goal state:\n{}\n", initial_node.grid, goal);
-        eprintln!("As reminder here are the settings you requested:\n");
-        eprintln!("Algorithm:\t\t\t{}", a_type);
-        eprintln!("Heuristic:\t\t\t{}", h_type);
-        eprintln!("Maximum weight:\t\t\t{}", max_weight);
-        eprintln!("Greedy search:\t\t\t{}", greedy);
-        eprintln!("Uniform cost search max cost:\t{}", g_max);
-        std::process::exit(42);
+    if greedy && matches!(settings.a_type, AType::IDAStar | AType::AraStar)
+    {
+        return Err(Error::Usage(format!("It's not allowed to perform greedy search with {} algorithm\nPlease select another algorithm or remove the use of option `-g`", settings.a_type)));
     }
-}
-
-
-/* #[cfg(test)]
-mod tests
-{
-    use super::*;
-    use rand::seq::SliceRandom;
-    use rand::thread_rng;
-
-    #[test]
-    fn parsing_pass_three()
+    if let Some(path) = matches.value_of("batch")
     {
-        let mut rng = thread_rng();
-        let mut test_vec: Vec<u16> = (0..9).collect();
-        test_vec.shuffle(&mut rng);
-        let test_str: String = test_vec.iter().map(|x| x.to_string() + " ").collect();
-        assert_eq!(parser(test_str).unwrap().1.get_map(), test_vec);
+        let format = usage(SummaryFormat::from_str_or_default(matches.value_of("summary_format")))?;
+        let stats = batch::solve_all(batch::collect_puzzles(path, input_format)?, input_format, &settings, jobs);
+        let summary = batch::write_summary(&stats, format);
+        match matches.value_of("summary")
+        {
+            Some(file) => fs::write(file, summary).map_err(|e| Error::io(file, e))?,
+            None => print!("{}", summary)
+        }
+        return Ok(());
     }
 
-    #[test]
-    fn parsing_pass_four()
+    let seed: u64 = match matches.value_of("seed")
+    {
+        Some(x) => x.parse().unwrap(),
+        None => rand::random()
+    };
+    let g_type = usage(GType::from_str_or_default(matches.value_of("generation")))?;
+    if let Some(lines) = matches.value_of("random")
     {
-        let mut rng = thread_rng();
-        let mut test_vec: Vec<u16> = (0..16).collect();
-        test_vec.shuffle(&mut rng);
-        let test_str: String = test_vec.iter().map(|x| x.to_string() + " ").collect();
-        assert_eq!(parser(test_str).unwrap().1.get_map(), test_vec);
+        // A single puzzle: the threads go to the search itself.
+        settings.jobs = jobs;
+        let grid = g_type.generate(lines.parse().unwrap(), &mut puzzle_gen::seeded_rng(seed));
+        let report = solve_report(grid, None, &settings, Some((seed, g_type)), style).expect("writing into a String never fails");
+        if let (Some((format, path)), None) = (render, &report.error)
+        {
+            render::write(&report.steps, format, path, delay)?;
+        }
+        if animate
+        {
+            animate::animate(&report.steps, delay)?;
+        }
+        print!("{}", report.out);
+        eprint!("{}", report.err);
+        return report.error.map_or(Ok(()), Err);
     }
 
-    #[test]
-    fn parsing_pass_five()
+    let inputs: Vec<Option<&str>> = match matches.values_of("input")
     {
-        let mut rng = thread_rng();
-        let mut test_vec: Vec<u16> = (0..25).collect();
-        test_vec.shuffle(&mut rng);
-        let test_str: String = test_vec.iter().map(|x| x.to_string() + " ").collect();
-        assert_eq!(parser(test_str).unwrap().1.get_map(), test_vec);
+        Some(files) => files.map(Some).collect(),
+        None => vec![None]
+    };
+    let total = inputs.len();
+    let several = total > 1;
+    if several && (animate || render.is_some())
+    {
+        return Err(Error::Usage(String::from("Only a single puzzle can be animated or rendered")));
     }
-
-    #[test]
-    fn parsing_pass_six()
+    if !several
     {
-        let mut rng = thread_rng();
-        let mut test_vec: Vec<u16> = (0..36).collect();
-        test_vec.shuffle(&mut rng);
-        let test_str: String = test_vec.iter().map(|x| x.to_string() + " ").collect();
-        assert_eq!(parser(test_str).unwrap().1.get_map(), test_vec);
+        settings.jobs = jobs;
     }
-
-    #[test]
-    fn parsing_fail_length()
+    style.stream = !several;
+    let puzzles: Vec<_> = inputs.into_iter().map(|input| {
+        let name = input.unwrap_or("-");
+        let puzzle = read_input(input).map_err(|e| Error::io(name, e)).and_then(|c| parse_puzzle(c, input_format).map_err(Error::from));
+        (String::from(name), puzzle)
+    }).collect();
+    let reports = utils::run_parallel(puzzles, jobs, |(name, puzzle)| {
+        let mut report = match puzzle
+        {
+            Ok((grid, goal)) => solve_report(grid, goal, &settings, None, style).expect("writing into a String never fails"),
+            Err(e) => Report { out: String::new(), err: String::new(), error: Some(e), steps: Vec::new() }
+        };
+        if several
+        {
+            report.out.insert_str(0, &format!("==> {} <==\n", name));
+        }
+        (name, report)
+    });
+    let mut errors: Vec<Error> = Vec::new();
+    for (name, report) in reports
     {
-        let test_vec: Vec<u16> = (0..37).collect();
-        let test_str: String = test_vec.iter().map(|x| x.to_string() + " ").collect();
-        assert_eq!(parser(test_str), Err("Invalid puzzle format".into()));
+        if let (Some((format, path)), None) = (render, &report.error)
+        {
+            render::write(&report.steps, format, path, delay)?;
+        }
+        if animate
+        {
+            animate::animate(&report.steps, delay)?;
+        }
+        print!("{}", report.out);
+        eprint!("{}", report.err);
+        if let Some(error) = report.error
+        {
+            // With a single puzzle the error is printed by `main`.
+            if several
+            {
+                eprintln!("ERROR: {}: {}", name, error);
+            }
+            errors.push(error);
+        }
     }
-
-    #[test]
-    fn parsing_fail_char()
+    match errors.len()
     {
-        let mut test_str: String = "0 1 2 3 4 5 6 7 W 9".into();
-        assert_eq!(parser(test_str), Err("Invalid puzzle format".into()));
-        test_str = "D W 2 D 4 F 6 7 W 9".into();
-        assert_eq!(parser(test_str), Err("Invalid puzzle format".into()));
-        test_str = "0 1 2 3 4 5 6 7 8 9 W Q A X C W".into();
-        assert_eq!(parser(test_str), Err("Invalid puzzle format".into()));
+        0 => Ok(()),
+        failed if several => Err(Error::Failed { failed, total, first: Box::new(errors.remove(0)) }),
+        _ => Err(errors.remove(0))
     }
-} */
\ No newline at end of file
+}
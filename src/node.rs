@@ -1,7 +1,5 @@
 use std::cmp::{Ordering, Ord};
-use std::rc::Rc;
-use std::cell::RefCell;
-use crate::{state::State, grid::{Grid, HType}};
+use crate::{state::State, grid::{Grid, HType}, cost::CType};
 use std::hash::{Hash, Hasher};
 
 #[derive(Eq, Clone, Debug)]
@@ -9,7 +7,9 @@ pub struct Node
 {
     pub grid: Grid,
     pub state: State,
-    pub parent: Option<Rc<RefCell<Node>>>,
+    // Index of the parent in the list of nodes it is stored in (see `Algo`), nodes don't own each other
+    // so they stay `Send` and can be moved to another thread.
+    pub parent: Option<usize>,
 }
 
 impl Hash for Node
@@ -40,7 +40,7 @@ impl PartialOrd for Node
 {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering>
     {
-        Some(self.state.cmp(&other.state))
+        Some(self.cmp(other))
     }
 }
 
@@ -56,38 +56,29 @@ impl Node
         }
     }
 
-    pub fn generate_childs(node: Rc<RefCell<Self>>) -> Vec<Rc<RefCell<Node>>>
+    pub fn generate_childs(&self, parent: usize, cost: &CType) -> Vec<Node>
     {
-        let mut ret:Vec<Rc<RefCell<Node>>> = Vec::new();
-        for grid in node.borrow().grid.move_all_possible()
+        let mut ret: Vec<Node> = Vec::new();
+        for grid in self.grid.move_all_possible()
         {
-            ret.push(Rc::new(RefCell::new(Node
+            let move_cost = self.grid.get_moved_tile(&grid).map_or(1, |tile| cost.of(tile));
+            ret.push(Node
             {
                 grid,
-                state: State::new(0, node.borrow().state.g + 1, 0),
-                parent: Some(Rc::clone(&node)),
-            })));
+                state: State::new(0, self.state.g + move_cost, 0),
+                parent: Some(parent),
+            });
         }
         ret
     }
 
-    pub fn update_state(&mut self, goal: &Grid, h_type: HType, weight: u32, greedy: bool)
+    pub fn update_state(&mut self, goal: &Grid, h_type: HType, weight: u32, greedy: bool, cost: &CType)
     {
         match h_type
         {
-            HType::Hamming => self.state.update_hamming(&self.grid, goal, weight, greedy),
-            HType::Manhattan => self.state.update_manhattan(&self.grid, goal, weight, greedy),
-            HType::LinearManhattan => self.state.update_linear_manhattan(&self.grid, goal, weight, greedy)
+            HType::Hamming => self.state.update_hamming(&self.grid, goal, weight, greedy, cost),
+            HType::Manhattan => self.state.update_manhattan(&self.grid, goal, weight, greedy, cost),
+            HType::LinearManhattan => self.state.update_linear_manhattan(&self.grid, goal, weight, greedy, cost)
         }
     }
-
-    pub fn print_steps(&self)
-    {
-        if let Some(parent) = &self.parent
-        {
-            parent.borrow().print_steps();
-        }
-        println!("{}", self.grid);
-        println!("===================================\n");
-    }
 }
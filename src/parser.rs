@@ -0,0 +1,448 @@
+use std::{fmt, convert::TryFrom};
+use serde_json::{Map, Value};
+use crate::grid::{Grid, WALL};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseError
+{
+    Empty,
+    InvalidSize(String),
+    // The size line announces `expected` rows but the puzzle has `found`.
+    RowCount { expected: usize, found: usize },
+    RowLength { line: usize, expected: usize, found: usize },
+    InvalidToken { line: usize, column: usize, token: String },
+    DuplicateTile(u16),
+    MissingTile(u16),
+    OutOfRange { tile: u16, max: u16 },
+    NoBlank,
+    InvalidJson { line: usize, column: usize, reason: String },
+    MissingField(String),
+    // A JSON field holds something else than expected.
+    InvalidField { field: String, reason: String },
+    // The goal given with the puzzle does not hold the same tiles and walls.
+    GoalMismatch
+}
+
+impl fmt::Display for ParseError
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self
+        {
+            Self::Empty => write!(f, "There is no way we can resolve an empty puzzle dummy!"),
+            Self::InvalidSize(size) => write!(f, "Invalid puzzle size: [{}]", size),
+            Self::RowCount { expected, found } => write!(f, "The size definition and the number of line of the puzzle don't match: expected {} lines, found {}", expected, found),
+            Self::RowLength { line, expected, found } => write!(f, "Line {}: expected {} columns, found {}", line, expected, found),
+            Self::InvalidToken { line, column, token } => write!(f, "Line {}, column {}: invalid token [{}]", line, column, token),
+            Self::DuplicateTile(tile) => write!(f, "Tile {} appears more than once", tile),
+            Self::MissingTile(tile) => write!(f, "Tile {} is missing", tile),
+            Self::OutOfRange { tile, max } => write!(f, "Tile {} is out of range, tiles go from 1 to {}", tile, max),
+            Self::NoBlank => write!(f, "The puzzle has no blank (0)"),
+            Self::InvalidJson { line, column, reason } => write!(f, "Line {}, column {}: invalid JSON, {}", line, column, reason),
+            Self::MissingField(field) => write!(f, "The field `{}` is missing", field),
+            Self::InvalidField { field, reason } => write!(f, "The field `{}` {}", field, reason),
+            Self::GoalMismatch => write!(f, "The goal does not hold the same tiles and walls as the puzzle")
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum InputFormat
+{
+    // Picked from the first line of the input.
+    #[default]
+    Auto,
+    // Size line then one line per row, what `parser` reads.
+    School,
+    // `3: 1 2 3 8 0 4 7 6 5`
+    Line,
+    // One row per line, tiles separated by commas.
+    Csv,
+    // `{ "size": 3, "tiles": [...], "goal": [...] }`, the goal being optional.
+    Json
+}
+
+impl fmt::Display for InputFormat
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self
+        {
+            Self::Auto => write!(f, "Auto"),
+            Self::School => write!(f, "42"),
+            Self::Line => write!(f, "Single line"),
+            Self::Csv => write!(f, "CSV"),
+            Self::Json => write!(f, "JSON")
+        }
+    }
+}
+
+impl InputFormat
+{
+    pub fn from_str_or_default(input: Option<&str>) -> Result<Self, String>
+    {
+        match input
+        {
+            None => Ok(Self::default()),
+            Some("auto") => Ok(Self::Auto),
+            Some("42") => Ok(Self::School),
+            Some("line") => Ok(Self::Line),
+            Some("csv") => Ok(Self::Csv),
+            Some("json") => Ok(Self::Json),
+            Some(i) => Err(format!("This input format does not exist: {}", i))
+        }
+    }
+
+    // The format `Auto` stands for with this input, any other format is returned as is.
+    pub fn detect(self, content: &str) -> Self
+    {
+        if self != Self::Auto
+        {
+            return self;
+        }
+        match numbered_lines(content).first()
+        {
+            Some((_, line)) if line.starts_with('{') => Self::Json,
+            Some((_, line)) if line.contains(':') => Self::Line,
+            Some((_, line)) if line.contains(',') => Self::Csv,
+            _ => Self::School
+        }
+    }
+}
+
+// Walls are not tiles and there can be several blanks, the remaining tiles must be exactly `1..=len`.
+fn check_tiles(input: &[u16]) -> Result<(), ParseError>
+{
+    if !input.contains(&0)
+    {
+        return Err(ParseError::NoBlank);
+    }
+    // No tile can be larger than the number of cells minus the blank, whatever the walls.
+    let max = input.len() as u16 - 1;
+    let mut seen = vec![false; input.len()];
+    for &tile in input.iter().filter(|&&x| x != WALL && x != 0)
+    {
+        if tile > max
+        {
+            return Err(ParseError::OutOfRange { tile, max });
+        }
+        else if seen[tile as usize]
+        {
+            return Err(ParseError::DuplicateTile(tile));
+        }
+        seen[tile as usize] = true;
+    }
+    let len = input.iter().filter(|&&x| x != WALL && x != 0).count();
+    match (1..=len).find(|&tile| !seen[tile])
+    {
+        Some(tile) => Err(ParseError::MissingTile(tile as u16)),
+        None => Ok(())
+    }
+}
+
+// What every format checks once the tiles are read: `lines` rows of `lines` cells, see `check_tiles`.
+// `field` names the tiles in the error when there are not as many as the cells.
+pub fn check_map(field: &str, map: &[u16], lines: usize) -> Result<(), ParseError>
+{
+    check_size(lines)?;
+    if map.len() != lines * lines
+    {
+        return Err(invalid_field(field, format!("must hold {} tiles, found {}", lines * lines, map.len())));
+    }
+    check_tiles(map)
+}
+
+// Lines left once comments are removed, with their number in the file (starting at 1).
+fn numbered_lines(content: &str) -> Vec<(usize, String)>
+{
+    content.lines().enumerate()
+        .filter_map(|(i, line)| utils::remove_comment_by_line(line, "#").pop().map(|l| (i + 1, l)))
+        .collect()
+}
+
+// Sizes must fit the u8 line count of a grid.
+fn check_size(size: usize) -> Result<usize, ParseError>
+{
+    if size == 0 || size > u8::MAX as usize
+    {
+        return Err(ParseError::InvalidSize(size.to_string()));
+    }
+    Ok(size)
+}
+
+fn parse_size(size: &str) -> Result<usize, ParseError>
+{
+    let size = size.trim();
+    size.parse::<usize>().map_err(|_| ParseError::InvalidSize(String::from(size))).and_then(check_size)
+}
+
+fn parse_token(token: &str, line: usize, column: usize) -> Result<u16, ParseError>
+{
+    if token == "X" || token == "x"
+    {
+        return Ok(WALL);
+    }
+    match token.parse::<u16>()
+    {
+        Ok(tile) if tile != WALL => Ok(tile),
+        _ => Err(ParseError::InvalidToken { line, column, token: String::from(token) })
+    }
+}
+
+// Parses the rows of a square puzzle, `tokens` splits a line into its tiles.
+fn parse_rows(rows: Vec<(usize, String)>, nb_lines: usize, tokens: fn(&str) -> Vec<&str>) -> Result<Grid, ParseError>
+{
+    let mut ret: Vec<u16> = Vec::new();
+
+    if nb_lines != rows.len()
+    {
+        return Err(ParseError::RowCount { expected: nb_lines, found: rows.len() });
+    }
+    for (line, content_line) in rows
+    {
+        let row = tokens(&content_line);
+        for (column, token) in row.iter().enumerate()
+        {
+            ret.push(parse_token(token, line, column + 1)?);
+        }
+        if row.len() != nb_lines
+        {
+            return Err(ParseError::RowLength { line, expected: nb_lines, found: row.len() });
+        }
+    }
+    check_tiles(&ret)?;
+    Ok(Grid::new(ret, nb_lines as u8))
+}
+
+pub fn parser(content: String) -> Result<Grid, ParseError>
+{
+    let mut content_lines = numbered_lines(&content);
+
+    if content_lines.is_empty()
+    {
+        return Err(ParseError::Empty);
+    }
+    let (_, first) = content_lines.remove(0);
+    let nb_lines = parse_size(&first)?;
+    parse_rows(content_lines, nb_lines, |line| line.split_whitespace().collect())
+}
+
+fn parse_line(content: &str) -> Result<Grid, ParseError>
+{
+    let content_lines = numbered_lines(content);
+    let (line, content_line) = match &content_lines[..]
+    {
+        [] => return Err(ParseError::Empty),
+        [first] => first,
+        _ => return Err(ParseError::RowCount { expected: 1, found: content_lines.len() })
+    };
+    let (size, tiles) = content_line.split_once(':').ok_or_else(|| ParseError::InvalidSize(content_line.clone()))?;
+    let nb_lines = parse_size(size)?;
+    let tiles: Vec<&str> = tiles.split_whitespace().collect();
+    if tiles.len() != nb_lines * nb_lines
+    {
+        return Err(ParseError::RowLength { line: *line, expected: nb_lines * nb_lines, found: tiles.len() });
+    }
+    // Columns count the tiles of the whole line, not of a row.
+    let map = tiles.iter().enumerate().map(|(column, token)| parse_token(token, *line, column + 1)).collect::<Result<Vec<u16>, _>>()?;
+    check_tiles(&map)?;
+    Ok(Grid::new(map, nb_lines as u8))
+}
+
+fn parse_csv(content: &str) -> Result<Grid, ParseError>
+{
+    let rows = numbered_lines(content);
+    if rows.is_empty()
+    {
+        return Err(ParseError::Empty);
+    }
+    let nb_lines = check_size(rows.len())?;
+    parse_rows(rows, nb_lines, |row| row.split(',').map(str::trim).collect())
+}
+
+fn invalid_field(field: &str, reason: String) -> ParseError
+{
+    ParseError::InvalidField { field: String::from(field), reason }
+}
+
+// Tiles of a JSON puzzle: numbers, or "X" for the walls.
+fn json_grid(field: &str, value: &Value, nb_lines: usize) -> Result<Grid, ParseError>
+{
+    let items = value.as_array().ok_or_else(|| invalid_field(field, String::from("must be an array")))?;
+    let map = items.iter().enumerate().map(|(i, item)| match item
+    {
+        Value::Number(tile) => tile.as_u64().and_then(|t| u16::try_from(t).ok()).filter(|&t| t != WALL),
+        Value::String(wall) if wall == "X" || wall == "x" => Some(WALL),
+        _ => None
+    }.ok_or_else(|| invalid_field(field, format!("holds {} at index {}, tiles must be numbers or \"X\"", item, i)))).collect::<Result<Vec<u16>, _>>()?;
+    check_map(field, &map, nb_lines)?;
+    Ok(Grid::new(map, nb_lines as u8))
+}
+
+fn parse_json(content: &str) -> Result<(Grid, Option<Grid>), ParseError>
+{
+    let fields: Map<String, Value> = serde_json::from_str(content).map_err(|e| {
+        // The position is given apart, strip it from serde_json's message.
+        let reason = e.to_string();
+        let suffix = format!(" at line {} column {}", e.line(), e.column());
+        ParseError::InvalidJson { line: e.line(), column: e.column(), reason: String::from(reason.strip_suffix(&suffix).unwrap_or(&reason)) }
+    })?;
+
+    let nb_lines = match fields.get("size")
+    {
+        Some(Value::Number(size)) => size.as_u64().map_or_else(|| Err(ParseError::InvalidSize(size.to_string())), |s| check_size(s as usize))?,
+        Some(_) => return Err(invalid_field("size", String::from("must be a number"))),
+        None => return Err(ParseError::MissingField(String::from("size")))
+    };
+    let tiles = fields.get("tiles").ok_or_else(|| ParseError::MissingField(String::from("tiles")))?;
+    let grid = json_grid("tiles", tiles, nb_lines)?;
+    let goal = match fields.get("goal")
+    {
+        None | Some(Value::Null) => None,
+        Some(goal) => Some(json_grid("goal", goal, nb_lines)?)
+    };
+    if let Some(goal) = &goal
+    {
+        let mut tiles = grid.get_map().to_vec();
+        let mut goal_tiles = goal.get_map().to_vec();
+        if grid.get_walls() != goal.get_walls()
+        {
+            return Err(ParseError::GoalMismatch);
+        }
+        tiles.sort_unstable();
+        goal_tiles.sort_unstable();
+        if tiles != goal_tiles
+        {
+            return Err(ParseError::GoalMismatch);
+        }
+    }
+    Ok((grid, goal))
+}
+
+// Reads a puzzle in any supported format, only JSON can give a goal other than the snail.
+pub fn parse_puzzle(content: String, format: InputFormat) -> Result<(Grid, Option<Grid>), ParseError>
+{
+    match format.detect(&content)
+    {
+        InputFormat::Json => parse_json(&content),
+        InputFormat::Line => Ok((parse_line(&content)?, None)),
+        InputFormat::Csv => Ok((parse_csv(&content)?, None)),
+        InputFormat::School | InputFormat::Auto => Ok((parser(content)?, None))
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use rand::{seq::SliceRandom, thread_rng};
+
+    fn shuffled_puzzle(lines: u16) -> (Vec<u16>, String)
+    {
+        let mut test_vec: Vec<u16> = (0..lines * lines).collect();
+        test_vec.shuffle(&mut thread_rng());
+        let rows: Vec<String> = test_vec.chunks(lines as usize)
+            .map(|row| row.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(" "))
+            .collect();
+        (test_vec, format!("# shuffled\n{}\n{}\n", lines, rows.join("\n")))
+    }
+
+    #[test]
+    fn parsing_pass_three_to_six()
+    {
+        for lines in 3..=6
+        {
+            let (test_vec, test_str) = shuffled_puzzle(lines);
+            assert_eq!(parser(test_str).unwrap().get_map(), &test_vec[..]);
+        }
+    }
+
+    #[test]
+    fn parsing_fail_header()
+    {
+        assert_eq!(parser(String::from("# nothing\n\n")), Err(ParseError::Empty));
+        assert_eq!(parser(String::from("three\n1 2 3")), Err(ParseError::InvalidSize(String::from("three"))));
+        assert_eq!(parser(String::from("0\n")), Err(ParseError::InvalidSize(String::from("0"))));
+        assert_eq!(parser(String::from("3\n1 2 3\n8 0 4")), Err(ParseError::RowCount { expected: 3, found: 2 }));
+    }
+
+    #[test]
+    fn parsing_fail_length()
+    {
+        let test_str = String::from("3\n1 2 3\n# comment\n8 0 4 9\n7 6 5");
+        assert_eq!(parser(test_str), Err(ParseError::RowLength { line: 4, expected: 3, found: 4 }));
+    }
+
+    #[test]
+    fn parsing_fail_char()
+    {
+        let test_str = String::from("3\n1 2 3\n8 0 W # comment\n7 6 5");
+        assert_eq!(parser(test_str), Err(ParseError::InvalidToken { line: 3, column: 3, token: String::from("W") }));
+        let test_str = String::from("3\n1 2 -3\n8 0 4\n7 6 5");
+        assert_eq!(parser(test_str), Err(ParseError::InvalidToken { line: 2, column: 3, token: String::from("-3") }));
+    }
+
+    #[test]
+    fn parsing_fail_tiles()
+    {
+        assert_eq!(parser(String::from("3\n1 2 3\n8 0 4\n7 6 3")), Err(ParseError::DuplicateTile(3)));
+        assert_eq!(parser(String::from("3\n1 2 3\n8 0 4\n7 6 9")), Err(ParseError::OutOfRange { tile: 9, max: 8 }));
+        assert_eq!(parser(String::from("3\n1 2 3\n8 0 4\n7 0 5")), Err(ParseError::MissingTile(6)));
+        assert_eq!(parser(String::from("3\n1 2 3\nX 0 4\n7 6 8")), Err(ParseError::MissingTile(5)));
+        assert_eq!(parser(String::from("3\n1 2 3\n8 1 4\n7 6 5")), Err(ParseError::NoBlank));
+        assert!(parser(String::from("3\n1 2 3\nX 0 4\n7 6 5")).is_ok());
+        assert!(parser(String::from("3\n1 2 3\n7 0 4\n6 0 5")).is_ok());
+    }
+
+    #[test]
+    fn detect_format()
+    {
+        assert_eq!(InputFormat::Auto.detect("# c\n3\n1 2 3"), InputFormat::School);
+        assert_eq!(InputFormat::Auto.detect("3: 1 2 3 8 0 4 7 6 5"), InputFormat::Line);
+        assert_eq!(InputFormat::Auto.detect("1,2,3\n8,0,4\n7,6,5"), InputFormat::Csv);
+        assert_eq!(InputFormat::Auto.detect("  { \"size\": 3 }"), InputFormat::Json);
+        assert_eq!(InputFormat::Csv.detect("3: 1 2 3 8 0 4 7 6 5"), InputFormat::Csv);
+    }
+
+    #[test]
+    fn parsing_other_formats()
+    {
+        let expected = vec!(1, 2, 3, 8, 0, 4, 7, 6, 5);
+        let contents = [
+            "3: 1 2 3 8 0 4 7 6 5",
+            "# csv\n1, 2, 3\n8, 0, 4\n7, 6, 5\n",
+            "{\n  \"size\": 3,\n  \"tiles\": [1, 2, 3, 8, 0, 4, 7, 6, 5]\n}"
+        ];
+        for content in contents
+        {
+            let (grid, goal) = parse_puzzle(String::from(content), InputFormat::Auto).unwrap();
+            assert_eq!(grid.get_map(), &expected[..]);
+            assert!(goal.is_none());
+        }
+        let (grid, goal) = parse_puzzle(String::from("{\"size\": 2, \"tiles\": [1, \"X\", 0, 2], \"goal\": [1, \"X\", 2, 0]}"), InputFormat::Json).unwrap();
+        assert_eq!(grid.get_map(), &[1, WALL, 0, 2]);
+        assert_eq!(goal.unwrap().get_map(), &[1, WALL, 2, 0]);
+    }
+
+    #[test]
+    fn parsing_other_formats_fail()
+    {
+        let parse = |content: &str| parse_puzzle(String::from(content), InputFormat::Auto);
+        assert_eq!(parse("3: 1 2 3 8 0 4 7 6").err(), Some(ParseError::RowLength { line: 1, expected: 9, found: 8 }));
+        assert_eq!(parse("1,2,3\n8,0,4\n7,6").err(), Some(ParseError::RowLength { line: 3, expected: 3, found: 2 }));
+        assert_eq!(parse("1,2,3\n8,0,W\n7,6,5").err(), Some(ParseError::InvalidToken { line: 2, column: 3, token: String::from("W") }));
+        assert_eq!(parse("3: 1 2 3 8 0 W 7 6 5").err(), Some(ParseError::InvalidToken { line: 1, column: 6, token: String::from("W") }));
+        assert_eq!(parse("# size\n\n3: 1 2 3 8 0 4 7 6 -5").err(), Some(ParseError::InvalidToken { line: 3, column: 9, token: String::from("-5") }));
+        assert_eq!(parse("{\"tiles\": []}").err(), Some(ParseError::MissingField(String::from("size"))));
+        assert_eq!(parse("{\"size\": 2,\n \"tiles\": [1, 2, 3, true]}").err(),
+            Some(ParseError::InvalidField { field: String::from("tiles"), reason: String::from("holds true at index 3, tiles must be numbers or \"X\"") }));
+        assert_eq!(parse("{\"size\": 2, \"tiles\": [1, 2, 3]}").err(),
+            Some(ParseError::InvalidField { field: String::from("tiles"), reason: String::from("must hold 4 tiles, found 3") }));
+        assert_eq!(parse("{\"size\": 0, \"tiles\": []}").err(), Some(ParseError::InvalidSize(String::from("0"))));
+        assert_eq!(parse("{\"size\": 2, \"tiles\": [1, 2, 3, 0}").err(),
+            Some(ParseError::InvalidJson { line: 1, column: 33, reason: String::from("expected `,` or `]`") }));
+        assert_eq!(parse_puzzle(String::from("[1, 2]"), InputFormat::Json).err(),
+            Some(ParseError::InvalidJson { line: 1, column: 0, reason: String::from("invalid type: sequence, expected a map") }));
+        assert_eq!(parse("{\"size\": 2, \"tiles\": [1, 2, 3, 0], \"goal\": [1, 2, 0, \"X\"]}").err(), Some(ParseError::GoalMismatch));
+    }
+}
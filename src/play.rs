@@ -0,0 +1,146 @@
+use std::io::{self, Write};
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEventKind},
+    queue,
+    style::Print,
+};
+use n_puzzle::{
+    grid::{Grid, Move},
+    error::Error,
+};
+use crate::tui::{self, RawTerminal};
+
+// A game in progress: the grid the player moves and the goal they must reach.
+pub struct Game
+{
+    grid: Grid,
+    goal: Grid,
+    moves: u32
+}
+
+impl Game
+{
+    pub fn new(grid: Grid, goal: Grid) -> Self
+    {
+        Game { grid, goal, moves: 0 }
+    }
+
+    // Returns false when the blank can't go this way.
+    pub fn play(&mut self, mov: Move) -> bool
+    {
+        match self.grid.move_zero(mov)
+        {
+            Some(next) => {
+                self.grid = next;
+                self.moves += 1;
+                true
+            },
+            None => false
+        }
+    }
+
+    pub fn solved(&self) -> bool
+    {
+        self.grid == self.goal
+    }
+
+    pub fn distance(&self) -> u32
+    {
+        self.grid.manhattan(&self.goal)
+    }
+}
+
+// Arrow keys and WASD move the blank.
+fn key_to_move(code: KeyCode) -> Option<Move>
+{
+    match code
+    {
+        KeyCode::Up | KeyCode::Char('w') | KeyCode::Char('W') => Some(Move::Up),
+        KeyCode::Down | KeyCode::Char('s') | KeyCode::Char('S') => Some(Move::Down),
+        KeyCode::Right | KeyCode::Char('d') | KeyCode::Char('D') => Some(Move::Right),
+        KeyCode::Left | KeyCode::Char('a') | KeyCode::Char('A') => Some(Move::Left),
+        _ => None
+    }
+}
+
+fn draw(out: &mut impl Write, game: &Game, message: &str) -> io::Result<()>
+{
+    tui::draw_grid(out, &game.grid, None)?;
+    queue!(out, Print(format!("Moves: {}\tManhattan distance: {}\r\n\r\n", game.moves, game.distance())))?;
+    queue!(out, Print(format!("{}\r\n", message)))?;
+    out.flush()
+}
+
+fn run_game(game: &mut Game) -> io::Result<()>
+{
+    let _terminal = RawTerminal::enter()?;
+    let mut out = io::stdout();
+    let help = "Move the blank with the arrow keys or WASD, q to quit.";
+    let mut message = help;
+
+    loop
+    {
+        if game.solved()
+        {
+            draw(&mut out, game, "solved! Press any key to leave.")?;
+            loop
+            {
+                if let Event::Key(key) = event::read()?
+                {
+                    if key.kind == KeyEventKind::Press
+                    {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+        draw(&mut out, game, message)?;
+        let key = match event::read()?
+        {
+            Event::Key(key) if key.kind == KeyEventKind::Press => key,
+            _ => continue
+        };
+        message = match (key.code, key_to_move(key.code))
+        {
+            (KeyCode::Char('q') | KeyCode::Char('Q') | KeyCode::Esc, _) => return Ok(()),
+            (_, Some(mov)) if !game.play(mov) => "The blank can't go there.",
+            _ => help
+        };
+    }
+}
+
+// Lets the player solve `grid` by hand, the result is printed once the terminal is restored.
+pub fn play(grid: Grid, goal: Grid) -> Result<(), Error>
+{
+    let mut game = Game::new(grid, goal);
+    run_game(&mut game).map_err(|e| Error::io("terminal", e))?;
+    if game.solved()
+    {
+        println!("solved! ({} moves)", game.moves);
+    }
+    else {
+        println!("Gave up after {} moves, {} to go by the Manhattan distance", game.moves, game.distance());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn game()
+    {
+        let goal = Grid::new(vec!(1, 2, 3, 8, 0, 4, 7, 6, 5), 3);
+        let mut game = Game::new(Grid::new(vec!(1, 2, 3, 8, 4, 0, 7, 6, 5), 3), goal);
+        assert_eq!(game.distance(), 1);
+        assert!(!game.play(Move::Right));
+        assert!(!game.solved());
+        assert!(game.play(key_to_move(KeyCode::Char('a')).unwrap()));
+        assert!(game.solved());
+        assert_eq!(game.moves, 1);
+        assert_eq!(key_to_move(KeyCode::Up), Some(Move::Up));
+        assert_eq!(key_to_move(KeyCode::Char('q')), None);
+    }
+}
@@ -1,10 +1,240 @@
 extern crate rand;
 
+use std::{fmt, collections::HashSet, time::{Duration, Instant}};
+use rand::{rngs::StdRng, SeedableRng, Rng, seq::SliceRandom};
 use utils::snail_sort;
+use crate::{
+    grid::{Grid, HType, WALL},
+    node::Node,
+    state::State,
+    algo::{Algo, AType},
+    cost::CType
+};
+
+// Number of random walks tried before giving up on finding a 4x4 puzzle at the requested distance.
+const DISTANCE_ATTEMPTS: u32 = 1000;
+// Time after which no new walk is tried, the one being checked is still finished.
+const DISTANCE_BUDGET: Duration = Duration::from_secs(20);
+// Longest optimal solution of a 4x4 puzzle.
+const MAX_DISTANCE_4X4: u32 = 80;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum GType
+{
+    // Random moves of the blank from the goal, close to the goal when the puzzle is large.
+    #[default]
+    RandomWalk,
+    // Uniformly random solvable permutation, best suited for benchmarks.
+    Uniform
+}
+
+impl fmt::Display for GType
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self
+        {
+            Self::RandomWalk => write!(f, "Random walk"),
+            Self::Uniform => write!(f, "Uniform permutation")
+        }
+    }
+}
+
+impl GType
+{
+    pub fn from_str_or_default(input: Option<&str>) -> Result<Self, String>
+    {
+        match input
+        {
+            None => Ok(Self::default()),
+            Some("walk") => Ok(Self::RandomWalk),
+            Some("uniform") => Ok(Self::Uniform),
+            Some(g) => Err(format!("This generation mode does not exist: {}", g))
+        }
+    }
+
+    pub fn generate<R: Rng>(self, lines: u8, rng: &mut R) -> Grid
+    {
+        match self
+        {
+            Self::RandomWalk => Grid::new_random(lines, rng),
+            Self::Uniform => Grid::new_random_uniform(lines, rng)
+        }
+    }
+}
+
+// Random generator to hand to `Grid::new_random` so a puzzle can be generated again from its seed.
+pub fn seeded_rng(seed: u64) -> StdRng
+{
+    StdRng::seed_from_u64(seed)
+}
 
 pub fn create_snail_goal(lgth: u8) -> Vec<u16>
 {
     let mut ret: Vec<u16> = (1..lgth as u16 * lgth as u16).collect();
     ret.push(0);
     snail_sort(&ret, lgth)
-}
\ No newline at end of file
+}
+// Same as `create_snail_goal` but the cells listed in `walls` (absolute positions) are
+// left as walls and the snail simply skips them, the `blanks` blanks take the last free cells.
+pub fn create_snail_goal_with(lgth: u8, walls: &[u16], blanks: u16) -> Vec<u16>
+{
+    let size = lgth as u16 * lgth as u16;
+    let ranks = snail_sort(&(0..size).collect(), lgth);
+    let mut order: Vec<u16> = (0..size).collect();
+    order.sort_unstable_by_key(|&pos| ranks[pos as usize]);
+
+    let mut ret: Vec<u16> = vec![WALL; size as usize];
+    let free: Vec<u16> = order.into_iter().filter(|pos| !walls.contains(pos)).collect();
+    for (tile, pos) in free.iter().enumerate()
+    {
+        ret[*pos as usize] = if tile + blanks as usize >= free.len() { 0 } else { tile as u16 + 1 };
+    }
+    ret
+}
+
+// Default number of random moves used by `make_puzzle`, the same as the original Python generator.
+pub const DEFAULT_ITERATIONS: u32 = 10000;
+
+// Random walk of `iterations` moves from the goal, two tiles are then swapped when
+// an unsolvable puzzle is requested.
+pub fn make_puzzle<R: Rng>(lines: u8, solvable: bool, iterations: u32, rng: &mut R) -> Grid
+{
+    let puzzle = Grid::new_random_walk(lines, iterations, rng);
+    if solvable
+    {
+        puzzle
+    }
+    else {
+        puzzle.swap_tiles()
+    }
+}
+
+// Breadth-first layers of the state space around `goal`: `layers[k]` holds every grid at exactly
+// `k` moves from the goal. Stops after `max_depth` or when the whole space has been visited.
+pub fn distance_layers(goal: &Grid, max_depth: u32) -> Vec<Vec<Grid>>
+{
+    let mut seen: HashSet<Grid> = HashSet::new();
+    let mut layers: Vec<Vec<Grid>> = vec![vec![goal.clone()]];
+
+    seen.insert(goal.clone());
+    while (layers.len() as u32) <= max_depth
+    {
+        let mut next: Vec<Grid> = Vec::new();
+        for grid in layers.last().unwrap()
+        {
+            for child in grid.move_all_possible()
+            {
+                if !seen.contains(&child)
+                {
+                    seen.insert(child.clone());
+                    next.push(child);
+                }
+            }
+        }
+        if next.is_empty()
+        {
+            break;
+        }
+        layers.push(next);
+    }
+    layers
+}
+
+// Length of an optimal solution found with IDA* and the Manhattan distance (admissible).
+pub fn optimal_distance(grid: &Grid, goal: &Grid) -> Option<u32>
+{
+    let mut initial_node = Node::new(State::default(), grid.clone());
+    initial_node.update_state(goal, HType::Manhattan, 1, false, &CType::Unit);
+    let mut algo = Algo::new(initial_node, goal.clone(), HType::Manhattan, AType::IDAStar, CType::Unit, 1, 1, u32::MAX, false);
+    if algo.resolve()
+    {
+        Some(algo.get_total_cost())
+    }
+    else {
+        None
+    }
+}
+
+// Grid at exactly `distance` moves from the snail goal. A 3x3 grid is picked from the
+// breadth-first layers, a 4x4 one is a random walk checked against an optimal solver.
+// Each 4x4 walk costs a full IDA* solve: a few milliseconds up to 20 moves but seconds past 40,
+// where walks also rarely stay optimal. So the walks stop after `DISTANCE_ATTEMPTS` of them
+// or `DISTANCE_BUDGET`, whichever comes first.
+pub fn create_at_distance<R: Rng>(lines: u8, distance: u32, rng: &mut R) -> Result<Grid, String>
+{
+    let goal = Grid::new(create_snail_goal(lines), lines);
+    match lines
+    {
+        3 => distance_layers(&goal, distance).get(distance as usize).and_then(|layer| layer.choose(rng)).cloned()
+            .ok_or_else(|| format!("No 3x3 puzzle is {} moves away from the goal", distance)),
+        4 if distance > MAX_DISTANCE_4X4 => Err(format!("No 4x4 puzzle is {} moves away from the goal, the farthest are {} moves away", distance, MAX_DISTANCE_4X4)),
+        4 => {
+            let start = Instant::now();
+            (0..DISTANCE_ATTEMPTS).take_while(|_| start.elapsed() < DISTANCE_BUDGET)
+                .map(|_| Grid::new_random_walk(lines, distance, rng))
+                .find(|grid| optimal_distance(grid, &goal) == Some(distance))
+                .ok_or_else(|| format!("Could not find a 4x4 puzzle {} moves away from the goal within {} attempts or {} seconds",
+                    distance, DISTANCE_ATTEMPTS, DISTANCE_BUDGET.as_secs()))
+        },
+        _ => Err(String::from("Puzzles at an exact distance can only be generated for 3x3 and 4x4 sizes"))
+    }
+}
+
+// Every 3x3 grid as far as possible from the snail goal, along with their distance.
+pub fn create_hardest(lines: u8) -> Result<(Vec<Grid>, u32), String>
+{
+    if lines != 3
+    {
+        return Err(String::from("The hardest puzzles are only known for the 3x3 size"));
+    }
+    let mut layers = distance_layers(&Grid::new(create_snail_goal(lines), lines), u32::MAX);
+    let distance = layers.len() as u32 - 1;
+    Ok((layers.pop().unwrap_or_default(), distance))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn make_solvable_and_unsolvable()
+    {
+        let mut rng = seeded_rng(0);
+        for lines in 3..9
+        {
+            assert_eq!(make_puzzle(lines, true, 100, &mut rng).solvable(), Some(true));
+            assert_eq!(make_puzzle(lines, false, 100, &mut rng).solvable(), Some(false));
+        }
+        assert_eq!(make_puzzle(3, true, 0, &mut rng), Grid::new(create_snail_goal(3), 3));
+    }
+
+    #[test]
+    fn at_distance_three()
+    {
+        let goal = Grid::new(create_snail_goal(3), 3);
+        let grid = create_at_distance(3, 12, &mut seeded_rng(0)).unwrap();
+        assert_eq!(optimal_distance(&grid, &goal), Some(12));
+        assert!(create_at_distance(3, 40, &mut seeded_rng(0)).is_err());
+    }
+
+    #[test]
+    fn at_distance_four()
+    {
+        let goal = Grid::new(create_snail_goal(4), 4);
+        let grid = create_at_distance(4, 10, &mut seeded_rng(0)).unwrap();
+        assert_eq!(optimal_distance(&grid, &goal), Some(10));
+        let error = create_at_distance(4, 81, &mut seeded_rng(0)).unwrap_err();
+        assert!(error.contains("81"));
+    }
+
+    #[test]
+    fn hardest()
+    {
+        let (grids, distance) = create_hardest(3).unwrap();
+        let goal = Grid::new(create_snail_goal(3), 3);
+        assert!(!grids.is_empty());
+        assert_eq!(optimal_distance(&grids[0], &goal), Some(distance));
+        assert!(create_hardest(4).is_err());
+    }
+}
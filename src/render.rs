@@ -0,0 +1,250 @@
+use std::{fmt, fs, path::Path, time::Duration};
+use crate::{
+    grid::{Grid, WALL},
+    error::Error,
+};
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum RenderFormat
+{
+    // One SVG file per step.
+    #[default]
+    Svg,
+    // One PPM file per step.
+    Ppm,
+    // Every step side by side in a single SVG.
+    SvgStrip,
+    // A single SVG looping over the steps.
+    SvgAnimated
+}
+
+impl fmt::Display for RenderFormat
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self
+        {
+            Self::Svg => write!(f, "SVG"),
+            Self::Ppm => write!(f, "PPM"),
+            Self::SvgStrip => write!(f, "SVG strip"),
+            Self::SvgAnimated => write!(f, "Animated SVG")
+        }
+    }
+}
+
+impl RenderFormat
+{
+    pub fn from_str_or_default(input: Option<&str>) -> Result<Self, String>
+    {
+        match input
+        {
+            None => Ok(Self::default()),
+            Some("svg") => Ok(Self::Svg),
+            Some("ppm") => Ok(Self::Ppm),
+            Some("svg_strip") => Ok(Self::SvgStrip),
+            Some("svg_animated") => Ok(Self::SvgAnimated),
+            Some(r) => Err(format!("This render format does not exist: {}", r))
+        }
+    }
+}
+
+const SVG_CELL: usize = 60;
+// Space between two boards of a strip.
+const SVG_GAP: usize = 20;
+
+fn svg_header(width: usize, height: usize) -> String
+{
+    format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\" font-family=\"sans-serif\">\n", width, height, width, height)
+}
+
+// Tiles of one board, its top left corner at `x`.
+fn svg_board(grid: &Grid, x: usize) -> String
+{
+    let lines = grid.get_lines() as usize;
+    let mut ret = format!("<rect x=\"{}\" y=\"0\" width=\"{}\" height=\"{}\" fill=\"#f0f0f0\"/>\n", x, lines * SVG_CELL, lines * SVG_CELL);
+    for (i, &tile) in grid.get_map().iter().enumerate()
+    {
+        let (cx, cy) = (x + (i % lines) * SVG_CELL, (i / lines) * SVG_CELL);
+        match tile
+        {
+            0 => continue,
+            WALL => ret += &format!("<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#3c3c3c\"/>\n", cx, cy, SVG_CELL, SVG_CELL),
+            n => {
+                ret += &format!("<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"6\" fill=\"#4682b4\" stroke=\"#f0f0f0\" stroke-width=\"2\"/>\n",
+                    cx + 1, cy + 1, SVG_CELL - 2, SVG_CELL - 2);
+                ret += &format!("<text x=\"{}\" y=\"{}\" font-size=\"24\" fill=\"white\" text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>\n",
+                    cx + SVG_CELL / 2, cy + SVG_CELL / 2, n);
+            }
+        }
+    }
+    ret
+}
+
+pub fn svg(grid: &Grid) -> String
+{
+    let size = grid.get_lines() as usize * SVG_CELL;
+    svg_header(size, size) + &svg_board(grid, 0) + "</svg>\n"
+}
+
+pub fn svg_strip(steps: &[Grid]) -> String
+{
+    let size = steps.first().map_or(0, |g| g.get_lines() as usize * SVG_CELL);
+    let width = (steps.len() * (size + SVG_GAP)).saturating_sub(SVG_GAP);
+    let boards: String = steps.iter().enumerate().map(|(i, grid)| svg_board(grid, i * (size + SVG_GAP))).collect();
+    svg_header(width, size) + &boards + "</svg>\n"
+}
+
+// Every board is hidden but during its own share of the loop.
+pub fn svg_animated(steps: &[Grid], step: Duration) -> String
+{
+    let size = steps.first().map_or(0, |g| g.get_lines() as usize * SVG_CELL);
+    let n = steps.len();
+    let dur = step.as_secs_f64() * n as f64;
+    let mut ret = svg_header(size, size);
+    for (i, grid) in steps.iter().enumerate()
+    {
+        let (values, key_times) = match i
+        {
+            0 => (String::from("visible;hidden"), format!("0;{}", 1.0 / n as f64)),
+            i if i + 1 == n => (String::from("hidden;visible"), format!("0;{}", i as f64 / n as f64)),
+            i => (String::from("hidden;visible;hidden"), format!("0;{};{}", i as f64 / n as f64, (i + 1) as f64 / n as f64))
+        };
+        ret += "<g visibility=\"hidden\">\n";
+        if n > 1
+        {
+            ret += &format!("<animate attributeName=\"visibility\" values=\"{}\" keyTimes=\"{}\" dur=\"{}s\" calcMode=\"discrete\" repeatCount=\"indefinite\"/>\n",
+                values, key_times, dur);
+        }
+        else {
+            ret += "<set attributeName=\"visibility\" to=\"visible\"/>\n";
+        }
+        ret += &svg_board(grid, 0);
+        ret += "</g>\n";
+    }
+    ret + "</svg>\n"
+}
+
+// 3x5 pixel digits, the last glyph is the X of the walls.
+const FONT: [[u8; 5]; 11] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b001, 0b001, 0b001],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b010, 0b101, 0b101]
+];
+const GLYPH_SCALE: usize = 3;
+// Width of a glyph and the space after it.
+const GLYPH_STEP: usize = 4 * GLYPH_SCALE;
+
+// Binary PPM (P6), readable by most image tools without any library.
+pub fn ppm(grid: &Grid) -> Vec<u8>
+{
+    let lines = grid.get_lines() as usize;
+    let digits = (grid.get_map().len() - 1).to_string().len();
+    let cell = 40.max(digits * GLYPH_STEP + 10);
+    let size = lines * cell;
+    let mut pixels = vec![240u8; size * size * 3];
+    let mut fill = |x: usize, y: usize, w: usize, h: usize, rgb: [u8; 3]| {
+        for py in y..y + h
+        {
+            for px in x..x + w
+            {
+                pixels[(py * size + px) * 3..(py * size + px) * 3 + 3].copy_from_slice(&rgb);
+            }
+        }
+    };
+
+    for (i, &tile) in grid.get_map().iter().enumerate()
+    {
+        let (cx, cy) = ((i % lines) * cell, (i / lines) * cell);
+        let (color, glyphs): ([u8; 3], Vec<usize>) = match tile
+        {
+            0 => continue,
+            WALL => ([60, 60, 60], vec![10]),
+            n => ([70, 130, 180], n.to_string().bytes().map(|b| (b - b'0') as usize).collect())
+        };
+        fill(cx + 1, cy + 1, cell - 2, cell - 2, color);
+        let text_width = glyphs.len() * GLYPH_STEP - GLYPH_SCALE;
+        let (tx, ty) = (cx + (cell - text_width) / 2, cy + (cell - 5 * GLYPH_SCALE) / 2);
+        for (g, &glyph) in glyphs.iter().enumerate()
+        {
+            for (row, bits) in FONT[glyph].iter().enumerate()
+            {
+                for col in 0..3
+                {
+                    if bits & (0b100 >> col) != 0
+                    {
+                        fill(tx + g * GLYPH_STEP + col * GLYPH_SCALE, ty + row * GLYPH_SCALE, GLYPH_SCALE, GLYPH_SCALE, [255, 255, 255]);
+                    }
+                }
+            }
+        }
+    }
+    let mut ret = format!("P6\n{} {}\n255\n", size, size).into_bytes();
+    ret.extend(pixels);
+    ret
+}
+
+// Writes the steps of a solution to `path`, a directory for the formats giving a file per step.
+pub fn write(steps: &[Grid], format: RenderFormat, path: &str, step: Duration) -> Result<(), Error>
+{
+    let io = |e| Error::io(path, e);
+    match format
+    {
+        RenderFormat::SvgStrip => fs::write(path, svg_strip(steps)).map_err(io),
+        RenderFormat::SvgAnimated => fs::write(path, svg_animated(steps, step)).map_err(io),
+        RenderFormat::Svg | RenderFormat::Ppm => {
+            fs::create_dir_all(path).map_err(io)?;
+            for (i, grid) in steps.iter().enumerate()
+            {
+                let (name, content) = match format
+                {
+                    RenderFormat::Ppm => (format!("step_{:03}.ppm", i), ppm(grid)),
+                    _ => (format!("step_{:03}.svg", i), svg(grid).into_bytes())
+                };
+                let file = Path::new(path).join(name);
+                fs::write(&file, content).map_err(|e| Error::io(&file.to_string_lossy(), e))?;
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn render_svg()
+    {
+        let grid = Grid::new(vec!(1, 2, 3, 8, 0, WALL, 7, 6, 5), 3);
+        let image = svg(&grid);
+        assert!(image.starts_with("<svg") && image.ends_with("</svg>\n"));
+        assert_eq!(image.matches("<text").count(), 7);
+        assert!(image.contains(">8</text>"));
+        let strip = svg_strip(&[grid.clone(), grid.clone()]);
+        assert!(strip.contains(&format!("width=\"{}\"", 2 * 3 * SVG_CELL + SVG_GAP)));
+        let animated = svg_animated(&[grid.clone(), grid.clone(), grid], Duration::from_millis(500));
+        assert_eq!(animated.matches("<animate ").count(), 3);
+        assert!(animated.contains("dur=\"1.5s\""));
+    }
+
+    #[test]
+    fn render_ppm()
+    {
+        let grid = Grid::new(vec!(1, 2, 3, 8, 0, 4, 7, 6, 5), 3);
+        let image = ppm(&grid);
+        let header = b"P6\n120 120\n255\n";
+        assert!(image.starts_with(header));
+        assert_eq!(image.len(), header.len() + 120 * 120 * 3);
+        // The blank's cell is left with the background colour.
+        let center = header.len() + (60 * 120 + 60) * 3;
+        assert_eq!(image[center..center + 3], [240, 240, 240]);
+    }
+}
@@ -1,6 +1,7 @@
 use std::cmp::{Ordering, Ord};
-use crate::grid::Grid;
+use crate::{grid::Grid, cost::CType};
 #[derive(Eq, PartialEq, Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct State
 {
     pub h: u32,
@@ -12,25 +13,7 @@ impl PartialOrd for State
 {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering>
     {
-        Some(if other.f > self.f
-        {
-            Ordering::Greater
-        }
-        else if other.f < self.f
-        {
-            Ordering::Less
-        }
-        else if (other.h > self.h) || (other.h == self.h && other.g > self.g)
-        {
-            Ordering::Greater
-        }
-        else if (other.h < self.h) || (other.h == self.h && other.g < self.g)
-        {
-            Ordering::Less
-        }
-        else {
-            Ordering::Equal
-        })
+        Some(self.cmp(other))
     }
 }
 
@@ -81,21 +64,21 @@ impl State
         }
     }
     
-    pub fn update_hamming(&mut self, grid: &Grid, goal: &Grid, weight: u32, greedy: bool)
+    pub fn update_hamming(&mut self, grid: &Grid, goal: &Grid, weight: u32, greedy: bool, cost: &CType)
     {
-        self.h = grid.hamming(goal) * weight;
+        self.h = grid.hamming_with_cost(goal, cost).saturating_mul(weight);
         self.compute_f(greedy);
     }
     
-    pub fn update_manhattan(&mut self, grid: &Grid, goal: &Grid, weight: u32, greedy: bool)
+    pub fn update_manhattan(&mut self, grid: &Grid, goal: &Grid, weight: u32, greedy: bool, cost: &CType)
     {
-        self.h = grid.manhattan(goal) * weight;
+        self.h = grid.manhattan_with_cost(goal, cost).saturating_mul(weight);
         self.compute_f(greedy);
     }
     
-    pub fn update_linear_manhattan(&mut self, grid: &Grid, goal: &Grid, weight: u32, greedy: bool)
+    pub fn update_linear_manhattan(&mut self, grid: &Grid, goal: &Grid, weight: u32, greedy: bool, cost: &CType)
     {
-        self.h = grid.linear_manhattan(goal) * weight;
+        self.h = grid.linear_manhattan_with_cost(goal, cost).saturating_mul(weight);
         self.compute_f(greedy);
     }
 }
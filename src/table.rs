@@ -0,0 +1,185 @@
+use std::{fs, io};
+use utils::permutation;
+use crate::{
+    grid::{Grid, Move, Topology},
+    puzzle_gen::{create_snail_goal, distance_layers},
+    hint::{Hint, MoveCheck},
+    error::Error,
+};
+
+// Every 3x3 grid which can reach the snail goal: 9!/2.
+pub const TABLE_SIZE: usize = 181_440;
+// Orders of the eight tiles for a given position of the blank, only one parity is reachable.
+const ORDERS: usize = TABLE_SIZE / 9;
+const MAGIC: &[u8; 4] = b"NPDT";
+// Written where no grid ranks, an empty slot is a corrupted table.
+const UNKNOWN: u8 = u8::MAX;
+
+// Moving the blank never changes the parity of the order of the tiles on an odd width, so the
+// blank's cell and the rank of the first six tiles among the eight is a perfect rank.
+pub fn rank(map: &[u16]) -> usize
+{
+    let blank = map.iter().position(|&t| t == 0).unwrap_or(0);
+    let tiles: Vec<u16> = map.iter().filter(|&&t| t != 0).take(6).map(|&t| t - 1).collect();
+    blank * ORDERS + permutation::partial_rank(&tiles, 8) as usize
+}
+
+// Optimal number of moves of every solvable 3x3 grid to the snail goal, one byte per grid.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DistanceTable
+{
+    distances: Vec<u8>
+}
+
+impl DistanceTable
+{
+    // Breadth-first from the goal, a few seconds at most.
+    pub fn build() -> Self
+    {
+        let mut distances = vec![UNKNOWN; TABLE_SIZE];
+        let goal = Grid::new(create_snail_goal(3), 3);
+        for (distance, layer) in distance_layers(&goal, u32::MAX).iter().enumerate()
+        {
+            for grid in layer
+            {
+                distances[rank(grid.get_map())] = distance as u8;
+            }
+        }
+        DistanceTable { distances }
+    }
+
+    pub fn load(path: &str) -> Result<Self, Error>
+    {
+        let content = fs::read(path).map_err(|e| Error::io(path, e))?;
+        let invalid = |reason: &str| Error::io(path, io::Error::new(io::ErrorKind::InvalidData, reason));
+        let distances = content.strip_prefix(MAGIC).ok_or_else(|| invalid("not a distance table"))?;
+        if distances.len() != TABLE_SIZE || distances.contains(&UNKNOWN)
+        {
+            return Err(invalid("truncated or corrupted distance table"));
+        }
+        Ok(DistanceTable { distances: distances.to_vec() })
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), Error>
+    {
+        fs::write(path, [&MAGIC[..], &self.distances].concat()).map_err(|e| Error::io(path, e))
+    }
+
+    pub fn max_distance(&self) -> u32
+    {
+        self.distances.iter().copied().max().unwrap_or(0) as u32
+    }
+
+    // Only flat 3x3 grids with a single blank, no walls and the snail goal are in the table.
+    pub fn covers(grid: &Grid, goal: &Grid) -> bool
+    {
+        grid.get_lines() == 3 && grid.get_blanks() == 1 && !grid.has_walls() && grid.get_topology() == Topology::Flat
+            && goal.get_map() == create_snail_goal(3).as_slice() && grid.solvable_to(goal) == Some(true)
+    }
+
+    pub fn distance(&self, grid: &Grid, goal: &Grid) -> Option<u32>
+    {
+        if !Self::covers(grid, goal)
+        {
+            return None;
+        }
+        Some(self.distances[rank(grid.get_map())] as u32)
+    }
+
+    pub fn hint(&self, grid: &Grid, goal: &Grid) -> Option<Hint>
+    {
+        let distance = self.distance(grid, goal)?;
+        let next = [Move::Up, Move::Down, Move::Right, Move::Left].iter().copied()
+            .find(|&m| grid.move_zero(m).is_some_and(|next| self.distances[rank(next.get_map())] as u32 + 1 == distance))
+            .map(|m| (0, m));
+        Some(Hint { next, distance })
+    }
+
+    // `None` when the grid isn't in the table or the move isn't possible.
+    pub fn check_move(&self, grid: &Grid, goal: &Grid, (blank, mov): (usize, Move)) -> Option<MoveCheck>
+    {
+        let before = self.distance(grid, goal)?;
+        let after = self.distance(&grid.move_blank(blank, mov)?, goal)?;
+        Some(MoveCheck { before, after })
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::{
+        grid::HType,
+        algo::{Algo, AType, Settings},
+        cost::CType,
+    };
+    use std::{collections::HashSet, sync::OnceLock};
+
+    // Building the table and its layers takes most of the test run, they are shared by the tests.
+    fn table() -> &'static DistanceTable
+    {
+        static TABLE: OnceLock<DistanceTable> = OnceLock::new();
+        TABLE.get_or_init(DistanceTable::build)
+    }
+
+    fn layers() -> &'static [Vec<Grid>]
+    {
+        static LAYERS: OnceLock<Vec<Vec<Grid>>> = OnceLock::new();
+        LAYERS.get_or_init(|| distance_layers(&Grid::new(create_snail_goal(3), 3), u32::MAX))
+    }
+
+    #[test]
+    fn perfect_rank()
+    {
+        let ranks: HashSet<usize> = layers().iter().flatten().map(|g| rank(g.get_map())).collect();
+        assert_eq!(ranks.len(), TABLE_SIZE);
+        assert!(ranks.iter().all(|&r| r < TABLE_SIZE));
+    }
+
+    #[test]
+    fn distance_table()
+    {
+        let table = table();
+        assert_eq!(table.max_distance(), 30);
+        let goal = Grid::new(create_snail_goal(3), 3);
+        let grid = Grid::new(vec!(1, 2, 3, 0, 8, 4, 7, 6, 5), 3);
+        assert_eq!(table.hint(&grid, &goal), Some(Hint { next: Some((0, Move::Right)), distance: 1 }));
+        assert_eq!(table.check_move(&grid, &goal, (0, Move::Up)), Some(MoveCheck { before: 1, after: 2 }));
+        assert_eq!(table.distance(&grid.swap_tiles(), &goal), None);
+        assert_eq!(table.distance(&grid.clone().with_topology(Topology::Torus), &goal), None);
+
+        let path = std::env::temp_dir().join(format!("n_puzzle_table_{}", std::process::id()));
+        let path = path.to_str().unwrap();
+        table.save(path).unwrap();
+        assert_eq!(&DistanceTable::load(path).unwrap(), table);
+        fs::write(path, b"NPDT").unwrap();
+        assert!(matches!(DistanceTable::load(path), Err(Error::Io { .. })));
+        fs::remove_file(path).unwrap();
+    }
+
+    // The table is the ground truth: admissible heuristics never overestimate it and the
+    // optimal solvers agree with it.
+    #[test]
+    fn table_ground_truth()
+    {
+        let table = table();
+        let goal = Grid::new(create_snail_goal(3), 3);
+        for grid in layers().iter().flatten().step_by(97)
+        {
+            let distance = table.distance(grid, &goal).unwrap();
+            assert!(grid.manhattan(&goal) <= distance);
+            assert!(grid.linear_manhattan(&goal) <= distance);
+        }
+        for grid in layers().iter().filter_map(|layer| layer.first()).step_by(4)
+        {
+            let distance = table.distance(grid, &goal);
+            for a_type in [AType::AStar, AType::IDAStar, AType::Bfs]
+            {
+                let settings = Settings { h_type: HType::LinearManhattan, a_type, c_type: CType::Unit, max_weight: Some(1), ..Settings::default() };
+                let mut algo = Algo::with_settings(grid.clone(), goal.clone(), &settings);
+                assert!(algo.resolve());
+                assert_eq!(Some(algo.get_total_cost()), distance, "{} on\n{}", a_type, grid);
+            }
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use std::io::{self, Write};
+use crossterm::{
+    cursor, execute, queue,
+    style::{Print, PrintStyledContent, Stylize},
+    terminal::{self, ClearType},
+};
+use n_puzzle::grid::{Grid, WALL};
+
+// Raw mode on an alternate screen for as long as it lives, the terminal is restored on drop
+// even when we stop on an error.
+pub struct RawTerminal;
+
+impl RawTerminal
+{
+    pub fn enter() -> io::Result<Self>
+    {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(RawTerminal)
+    }
+}
+
+impl Drop for RawTerminal
+{
+    fn drop(&mut self)
+    {
+        let _ = execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+// Clears the screen and draws `grid` like its `Display` does, `highlight` is shown in reverse video.
+// Raw mode doesn't go back to the first column on a new line, hence the `\r`.
+pub fn draw_grid(out: &mut impl Write, grid: &Grid, highlight: Option<u16>) -> io::Result<()>
+{
+    queue!(out, terminal::Clear(ClearType::All), cursor::MoveTo(0, 0), Print("\r\n"))?;
+    for row in grid.get_map().chunks(grid.get_lines() as usize)
+    {
+        for &tile in row
+        {
+            let cell = match tile
+            {
+                0 => String::from("_"),
+                WALL => String::from("X"),
+                n => n.to_string()
+            };
+            queue!(out, Print("\t"))?;
+            if Some(tile) == highlight
+            {
+                queue!(out, PrintStyledContent(cell.reverse().bold()))?;
+            }
+            else {
+                queue!(out, Print(cell))?;
+            }
+        }
+        queue!(out, Print("\r\n\r\n"))?;
+    }
+    Ok(())
+}
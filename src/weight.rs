@@ -0,0 +1,156 @@
+use std::fmt;
+use crate::state::State;
+
+// How the heuristic is weighted during A* and IDA*. With a max weight `w` the solution costs at most
+// `w` times the optimal cost, the progressive policy only up to the highest weight it reached.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WType
+{
+    // f = g + w * h from the start.
+    Constant,
+    // The weight starts at 1 and goes up by one as the search goes on, see `Schedule`.
+    #[default]
+    Progressive,
+    // Pohl's dynamic weighting: the max weight at the start, down to 1 at the depth anticipated by
+    // the heuristic of the initial grid. The depth of a node is its cost so far.
+    Dynamic,
+    // pwXD (Chen & Sturtevant): f = g + h while h > g, then (g + (2w - 1) * h) / w, so the search
+    // is close to optimal around the start and greedier near the goal.
+    PiecewiseLinear
+}
+
+impl fmt::Display for WType
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self
+        {
+            Self::Constant => write!(f, "Constant"),
+            Self::Progressive => write!(f, "Progressive"),
+            Self::Dynamic => write!(f, "Dynamic (Pohl)"),
+            Self::PiecewiseLinear => write!(f, "Piecewise linear (pwXD)")
+        }
+    }
+}
+
+impl WType
+{
+    pub fn from_str_or_default(input: Option<&str>) -> Result<Self, String>
+    {
+        match input
+        {
+            None => Ok(Self::default()),
+            Some("constant") => Ok(Self::Constant),
+            Some("progressive") => Ok(Self::Progressive),
+            Some("dynamic") => Ok(Self::Dynamic),
+            Some("pwxd") => Ok(Self::PiecewiseLinear),
+            Some(w) => Err(format!("This weighting policy does not exist: {}", w))
+        }
+    }
+}
+
+// A policy along with what it needs to weight a node: the current weight (the max one but for
+// the progressive policy) and the raw heuristic of the initial grid.
+#[derive(Copy, Clone, Debug)]
+pub struct Weighting
+{
+    pub w_type: WType,
+    pub weight: u32,
+    pub anticipated: u32
+}
+
+impl Weighting
+{
+    // `state.h` must be the unweighted heuristic. pwXD priorities are multiplied by `w` to stay integers.
+    pub fn apply(self, state: &mut State)
+    {
+        let (g, h, w) = (state.g as u64, state.h as u64, self.weight as u64);
+        let n = self.anticipated as u64;
+        let (h, f) = match self.w_type
+        {
+            WType::Constant | WType::Progressive => (h * w, g + h * w),
+            WType::Dynamic => {
+                let h = if g < n { h + h.saturating_mul(w - 1).saturating_mul(n - g) / n } else { h };
+                (h, g + h)
+            },
+            WType::PiecewiseLinear if h > g => (h, w * (g + h)),
+            WType::PiecewiseLinear => (h, g + (2 * w - 1) * h)
+        };
+        state.h = h.min(u32::MAX as u64) as u32;
+        state.f = f;
+    }
+}
+
+// The progressive policy: the weight goes up by one every `every` events (nodes popped by A*,
+// threshold changes of IDA*), and the events needed grow by `growth` at each multiple of 5.
+pub struct Schedule
+{
+    count: u32,
+    every: u32,
+    growth: u32
+}
+
+impl Schedule
+{
+    pub fn new(every: u32, growth: u32) -> Self
+    {
+        Schedule { count: 0, every, growth }
+    }
+
+    // Called on each event, returns the weight to use from now on.
+    pub fn tick(&mut self, weight: u32, max_weight: u32) -> u32
+    {
+        self.count += 1;
+        if self.count < self.every || weight >= max_weight
+        {
+            return weight;
+        }
+        if (weight + 1).is_multiple_of(5)
+        {
+            self.every += self.growth;
+        }
+        self.count = 0;
+        weight + 1
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn weighted(w_type: WType, weight: u32, g: u32, h: u32) -> (u32, u64)
+    {
+        let mut state = State::new(h, g, 0);
+        Weighting { w_type, weight, anticipated: 20 }.apply(&mut state);
+        (state.h, state.f)
+    }
+
+    #[test]
+    fn policies()
+    {
+        assert_eq!(weighted(WType::Constant, 3, 4, 10), (30, 34));
+        assert_eq!(weighted(WType::Dynamic, 3, 0, 20), (60, 60));
+        assert_eq!(weighted(WType::Dynamic, 3, 10, 10), (20, 30));
+        assert_eq!(weighted(WType::Dynamic, 3, 25, 10), (10, 35));
+        assert_eq!(weighted(WType::PiecewiseLinear, 3, 4, 10), (10, 42));
+        assert_eq!(weighted(WType::PiecewiseLinear, 3, 10, 4), (4, 30));
+        // A weight of 1 always gives f = g + h.
+        for w_type in [WType::Constant, WType::Progressive, WType::Dynamic, WType::PiecewiseLinear]
+        {
+            assert_eq!(weighted(w_type, 1, 7, 5), (5, 12));
+        }
+    }
+
+    #[test]
+    fn progressive_schedule()
+    {
+        let mut schedule = Schedule::new(2, 1);
+        let mut weight = 1;
+        let weights: Vec<u32> = (0..16).map(|_| {
+            weight = schedule.tick(weight, 6);
+            weight
+        }).collect();
+        assert_eq!(weights, vec![1, 2, 2, 3, 3, 4, 4, 5, 5, 5, 6, 6, 6, 6, 6, 6]);
+    }
+}
@@ -49,6 +49,27 @@ impl Coord
         self.y -= 1;
     }
 
+    // Brings a coordinate which went past an edge back on the opposite edge (torus topology).
+    pub fn wrap(&mut self, col: u8)
+    {
+        self.x = self.x.rem_euclid(col as i16);
+        self.y = self.y.rem_euclid(col as i16);
+    }
+
+    pub fn manhattan(&self, other: &Coord) -> u32
+    {
+        (self.x - other.x).unsigned_abs() as u32 + (self.y - other.y).unsigned_abs() as u32
+    }
+
+    // Manhattan distance when the table wraps around its edges, each axis takes the shortest way.
+    pub fn toroidal_manhattan(&self, other: &Coord, col: u8) -> u32
+    {
+        let dx = (self.x - other.x).unsigned_abs() as u32;
+        let dy = (self.y - other.y).unsigned_abs() as u32;
+
+        dx.min(col as u32 - dx) + dy.min(col as u32 - dy)
+    }
+
     pub fn is_out_of_table(&self, col: u8) -> bool
     {
         if self.x < 0 || self.x >= col as i16 || self.y < 0 || self.y >= col as i16
@@ -227,6 +248,38 @@ mod tests
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn wrap()
+    {
+        let expected = Coord { x: 2, y: 0 };
+
+        let mut result = Coord { x: -1, y: 3 };
+        result.wrap(3);
+
+        assert_eq!(result, expected);
+        assert!(!result.is_out_of_table(3));
+    }
+
+    #[test]
+    fn manhattan()
+    {
+        let expected = 5;
+
+        let result = Coord { x: 0, y: 3 }.manhattan(&Coord { x: 2, y: 0 });
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn toroidal_manhattan()
+    {
+        let expected = 2;
+
+        let result = Coord { x: 0, y: 3 }.toroidal_manhattan(&Coord { x: 3, y: 0 }, 4);
+
+        assert_eq!(result, expected);
+    }
+
     #[test]
     fn is_out_of_table_false()
     {
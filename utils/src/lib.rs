@@ -1,4 +1,7 @@
 pub mod coord;
+pub mod permutation;
+
+use std::{sync::Mutex, thread};
 
 // This function take a `&str` as input and split it on new lines.
 // `start_with` is to pass the char or str we want to use as comment's start.
@@ -72,6 +75,36 @@ pub fn snail_sort(input: &Vec<u16>, maxabs: u8) -> Vec<u16>
 	output
 }
 
+// Runs `f` on every item with up to `jobs` threads, the results are in the same order as `items`.
+pub fn run_parallel<T, R, F>(items: Vec<T>, jobs: usize, f: F) -> Vec<R>
+where
+	T: Send,
+	R: Send,
+	F: Fn(T) -> R + Sync
+{
+	let len = items.len();
+	let queue = Mutex::new(items.into_iter().enumerate());
+	let results: Mutex<Vec<Option<R>>> = Mutex::new((0..len).map(|_| None).collect());
+
+	thread::scope(|scope| {
+		for _ in 0..jobs.clamp(1, len.max(1))
+		{
+			scope.spawn(|| loop {
+				let next = queue.lock().unwrap().next();
+				match next
+				{
+					Some((i, item)) => {
+						let result = f(item);
+						results.lock().unwrap()[i] = Some(result);
+					},
+					None => break
+				}
+			});
+		}
+	});
+	results.into_inner().unwrap().into_iter().map(|r| r.expect("every item is processed")).collect()
+}
+
 #[cfg(test)]
 mod tests
 {
@@ -143,6 +176,17 @@ mod tests
 		}
 	}
 
+	#[test]
+	fn run_parallel_keeps_order()
+	{
+		use crate::run_parallel;
+		let items: Vec<u64> = (0..50).collect();
+		let expected: Vec<u64> = items.iter().map(|x| x * x).collect();
+		assert_eq!(run_parallel(items.clone(), 4, |x| x * x), expected);
+		assert_eq!(run_parallel(items, 1, |x| x * x), expected);
+		assert!(run_parallel(Vec::<u64>::new(), 4, |x| x).is_empty());
+	}
+
 	#[test]
 	fn snail_three_by_three()
 	{
@@ -0,0 +1,96 @@
+// Lehmer codes: a permutation of `0..n` is ranked by its position in lexicographic order,
+// which gives a perfect hash of the states of a puzzle.
+
+// Number of ordered picks of `k` values out of `n`: n! / (n - k)!, none when `k > n`.
+pub fn count(n: usize, k: usize) -> u64
+{
+	if k > n
+	{
+		return 0;
+	}
+	(n - k + 1..=n).map(|x| x as u64).product()
+}
+
+// Rank of `items`, distinct values of `0..n`, among every ordered pick of `items.len()` of
+// them. With the positions of a few tiles this is the index of a pattern database.
+pub fn partial_rank(items: &[u16], n: usize) -> u64
+{
+	debug_assert!(items.iter().all(|&item| (item as usize) < n), "items must be below {}", n);
+	let mut used = vec![false; n];
+	let mut rank = 0;
+	for (i, &item) in items.iter().enumerate()
+	{
+		let smaller = used[..item as usize].iter().filter(|&&u| !u).count();
+		rank = rank * (n - i) as u64 + smaller as u64;
+		used[item as usize] = true;
+	}
+	rank
+}
+
+// Inverse of `partial_rank`, `rank` must be below `count(n, k)`.
+pub fn partial_unrank(mut rank: u64, k: usize, n: usize) -> Vec<u16>
+{
+	debug_assert!(rank < count(n, k), "rank {} is out of the {} picks of {} out of {}", rank, count(n, k), k, n);
+	let mut digits = vec![0; k];
+	for i in (0..k).rev()
+	{
+		let base = (n - i) as u64;
+		digits[i] = (rank % base) as usize;
+		rank /= base;
+	}
+	let mut unused: Vec<u16> = (0..n as u16).collect();
+	digits.into_iter().map(|d| unused.remove(d)).collect()
+}
+
+// Full permutations, `n!` must fit in a u64 (up to 20 items).
+pub fn rank(perm: &[u16]) -> u64
+{
+	partial_rank(perm, perm.len())
+}
+
+// `rank` must be below `n!`.
+pub fn unrank(rank: u64, n: usize) -> Vec<u16>
+{
+	partial_unrank(rank, n, n)
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	#[test]
+	fn full()
+	{
+		assert_eq!(rank(&[0, 1, 2, 3]), 0);
+		assert_eq!(rank(&[3, 2, 1, 0]), 23);
+		assert_eq!(rank(&[1, 0, 2, 3]), 6);
+		assert_eq!(unrank(6, 4), vec![1, 0, 2, 3]);
+		for r in 0..count(5, 5)
+		{
+			assert_eq!(rank(&unrank(r, 5)), r);
+		}
+		let big: Vec<u16> = (0..16).rev().collect();
+		assert_eq!(rank(&big), count(16, 16) - 1);
+		assert_eq!(unrank(count(16, 16) - 1, 16), big);
+	}
+
+	#[test]
+	fn partial()
+	{
+		assert_eq!(count(9, 3), 504);
+		assert_eq!(count(4, 0), 1);
+		assert_eq!(count(3, 4), 0);
+		assert_eq!(partial_rank(&[], 4), 0);
+		assert_eq!(partial_rank(&[8, 7, 6], 9), 503);
+		let mut seen = vec![false; count(6, 3) as usize];
+		for r in 0..count(6, 3)
+		{
+			let items = partial_unrank(r, 3, 6);
+			assert_eq!(items.len(), 3);
+			assert_eq!(partial_rank(&items, 6), r);
+			seen[r as usize] = true;
+		}
+		assert!(seen.iter().all(|&s| s));
+	}
+}